@@ -0,0 +1,86 @@
+//! mwp_hook.dll — WM_MOUSELEAVE suppression for the WebView2 render host.
+//!
+//! The app forwards desktop mouse input to WebView2 through the composition
+//! controller, so the render host window never holds the real cursor and
+//! Windows keeps sending it leave messages, which drop `:hover` in the page.
+//! The app loads this DLL into the render host's thread with
+//! `SetWindowsHookExW(WH_GETMESSAGE)` and marks the windows to protect:
+//!
+//! - `MWP_T` on `Chrome_RenderWidgetHostHWND`, `MWP_T2` on widgets nested
+//!   under it (newer WebView2 builds route hover to those).
+//! - `MWP_OFF` on the render host while suppression is switched off.
+//! - `MWP_E` on a marked window lets its next leave through (explicit leave).
+
+#![cfg(target_os = "windows")]
+
+use windows_sys::core::{w, PCWSTR};
+use windows_sys::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows_sys::Win32::UI::WindowsAndMessaging::{
+    CallNextHookEx, GetParent, GetPropW, RemovePropW, HC_ACTION, MSG, WM_NULL,
+};
+
+const WM_MOUSELEAVE_U32: u32 = 0x02A3;
+const WM_NCMOUSELEAVE_U32: u32 = 0x02A2;
+
+const PROP_RENDER_HOST: PCWSTR = w!("MWP_T");
+const PROP_RENDER_CHILD: PCWSTR = w!("MWP_T2");
+const PROP_SUPPRESSION_OFF: PCWSTR = w!("MWP_OFF");
+const PROP_EXPLICIT_LEAVE: PCWSTR = w!("MWP_E");
+/// How far up a `MWP_T2` widget may sit below its render host.
+const MAX_CHILD_DEPTH: usize = 8;
+
+/// `WH_GETMESSAGE` hook: turns leave messages for marked windows into
+/// WM_NULL before the window procedure sees them.
+///
+/// # Safety
+/// Only to be called by Windows as a `WH_GETMESSAGE` hook, with `lparam`
+/// pointing at the retrieved `MSG`.
+#[no_mangle]
+pub unsafe extern "system" fn mouseleave_hook_proc(
+    code: i32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if code == HC_ACTION as i32 && lparam != 0 {
+        let msg = &mut *(lparam as *mut MSG);
+        match msg.message {
+            WM_MOUSELEAVE_U32 | WM_NCMOUSELEAVE_U32 if should_suppress(msg.hwnd) => {
+                msg.message = WM_NULL;
+            }
+            _ => {}
+        }
+    }
+    CallNextHookEx(std::ptr::null_mut(), code, wparam, lparam)
+}
+
+unsafe fn should_suppress(hwnd: HWND) -> bool {
+    let Some(host) = render_host(hwnd) else {
+        return false;
+    };
+    if !GetPropW(host, PROP_SUPPRESSION_OFF).is_null() {
+        return false;
+    }
+    // One-shot: the flag is consumed by the leave it lets through
+    RemovePropW(hwnd, PROP_EXPLICIT_LEAVE).is_null()
+}
+
+/// The marked render host `hwnd` belongs to, if any.
+unsafe fn render_host(hwnd: HWND) -> Option<HWND> {
+    if !GetPropW(hwnd, PROP_RENDER_HOST).is_null() {
+        return Some(hwnd);
+    }
+    if GetPropW(hwnd, PROP_RENDER_CHILD).is_null() {
+        return None;
+    }
+    let mut parent = GetParent(hwnd);
+    for _ in 0..MAX_CHILD_DEPTH {
+        if parent.is_null() {
+            break;
+        }
+        if !GetPropW(parent, PROP_RENDER_HOST).is_null() {
+            return Some(parent);
+        }
+        parent = GetParent(parent);
+    }
+    None
+}
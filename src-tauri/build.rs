@@ -1,3 +1,21 @@
+use sha2::{Digest, Sha256};
+
+/// Built by `beforeBuildCommand`/`beforeDevCommand` (tauri.windows.conf.json)
+/// ahead of this crate and bundled next to the exe.
+const HOOK_DLL: &str = "../crates/hook-dll/target/release/mwp_hook.dll";
+
 fn main() {
+    // The app only loads a mwp_hook.dll whose hash matches the one it was built with
+    println!("cargo:rerun-if-changed={HOOK_DLL}");
+    let digest = std::fs::read(HOOK_DLL)
+        .map(|bytes| {
+            Sha256::digest(bytes)
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect::<String>()
+        })
+        .unwrap_or_default();
+    println!("cargo:rustc-env=MWP_HOOK_SHA256={digest}");
+
     tauri_build::build()
 }
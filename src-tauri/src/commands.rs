@@ -6,7 +6,8 @@ use crate::events::{AppEvent, EmitAppEvent};
 use log::info;
 
 use crate::system_monitor;
-pub use commands_core::{SystemInfo, UpdateInfo};
+use crate::updater;
+pub use commands_core::{SystemInfo, UpdateChannel, UpdateInfo};
 
 #[tauri::command]
 pub fn get_system_info() -> SystemInfo {
@@ -29,59 +30,83 @@ pub fn subscribe_system_data(categories: Vec<String>) {
     system_monitor::set_poll_categories(valid);
 }
 
+/// Push a `system-data-update` for the subscribed categories now rather than
+/// at the next tick, without the warmup cost of `get_system_data`.
+#[tauri::command]
+pub fn refresh_system_data_now() {
+    system_monitor::refresh_now();
+}
+
+/// One 0.0-1.0 "how busy is the machine" dial from CPU, memory and disk I/O,
+/// for wallpapers that just want to scale animation intensity.
+#[tauri::command]
+pub fn get_system_pressure() -> f32 {
+    system_monitor::system_pressure()
+}
+
+/// "physical" (default) hides loopback, VPN and VM adapters; "all" shows every
+/// interface; anything else is a comma-separated allowlist of names. Persisted.
+#[tauri::command]
+pub fn set_network_filter(mode: String) -> AppResult<()> {
+    let filter = commands_core::NetworkFilter::parse(&mode)?;
+    system_monitor::set_network_filter(filter);
+    crate::settings::update(|s| s.network_filter = mode);
+    Ok(())
+}
+
+/// Buffered `system-data-update` samples for one category, oldest first, so a
+/// freshly loaded graph can draw immediately. Only subscribed categories fill.
+#[tauri::command]
+pub fn get_metric_history(category: String) -> AppResult<Vec<system_monitor::MetricSample>> {
+    if !commands_core::HISTORY_CATEGORIES.contains(&category.as_str()) {
+        return Err(AppError::Validation(format!(
+            "No history for category: {}",
+            category
+        )));
+    }
+    Ok(system_monitor::get_history(&category))
+}
+
+/// Samples kept per category (1-3600). Persisted.
+#[tauri::command]
+pub fn set_history_length(n: usize) -> AppResult<()> {
+    commands_core::validate_history_length(n)?;
+    system_monitor::set_history_length(n);
+    crate::settings::update(|s| s.metric_history_length = n);
+    Ok(())
+}
+
+/// Stop background polling. The next non-empty subscription restarts it.
+#[tauri::command]
+pub fn stop_system_monitor() {
+    system_monitor::stop_monitor();
+}
+
 // ============================================================================
 // Auto-Update Commands
 // ============================================================================
 
-fn build_updater(
-    app: &tauri::AppHandle,
-    endpoint: Option<String>,
-) -> AppResult<tauri_plugin_updater::Updater> {
-    use tauri_plugin_updater::UpdaterExt;
-    if let Some(url) = endpoint {
-        commands_core::validate_updater_endpoint(&url)?;
-        let parsed: url::Url = url
-            .parse()
-            .map_err(|e| AppError::Updater(format!("Invalid URL: {}", e)))?;
-        app.updater_builder()
-            .endpoints(vec![parsed])
-            .map_err(|e| AppError::Updater(format!("Invalid endpoint: {}", e)))?
-            .build()
-            .map_err(|e| AppError::Updater(format!("Build failed: {}", e)))
-    } else {
-        app.updater()
-            .map_err(|e| AppError::Updater(format!("Updater not available: {}", e)))
-    }
-}
+/// Minimum delay between two `update-download-progress` events.
+const PROGRESS_EMIT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
 
 #[tauri::command]
 pub async fn check_for_updates(
     app: tauri::AppHandle,
     endpoint: Option<String>,
+    channel: Option<String>,
 ) -> AppResult<Option<UpdateInfo>> {
-    let updater = build_updater(&app, endpoint)?;
-
-    match updater.check().await {
-        Ok(Some(update)) => {
-            // Reject downgrades to prevent rollback attacks
-            commands_core::validate_update_version(env!("CARGO_PKG_VERSION"), &update.version)?;
-            info!("[updater] Update available: v{}", update.version);
-            Ok(Some(UpdateInfo {
-                version: update.version.clone(),
-                current_version: env!("CARGO_PKG_VERSION").to_string(),
-                body: update.body.clone(),
-                date: update.date.map(|d| d.to_string()),
-            }))
-        }
-        Ok(None) => Ok(None),
-        Err(e) => Err(AppError::Updater(format!("Update check failed: {}", e))),
-    }
+    let channel = match channel {
+        Some(name) => UpdateChannel::parse(Some(&name))?,
+        None => updater::configured_channel(),
+    };
+    updater::check(&app, endpoint, channel).await
 }
 
 #[tauri::command]
 pub async fn download_and_install_update(
     app: tauri::AppHandle,
     endpoint: Option<String>,
+    channel: Option<String>,
 ) -> AppResult<()> {
     let emit_status = |status: &str| {
         let _ = app.emit_app_event(&AppEvent::UpdateProgress {
@@ -89,8 +114,12 @@ pub async fn download_and_install_update(
         });
     };
 
+    let channel = match channel {
+        Some(name) => UpdateChannel::parse(Some(&name))?,
+        None => updater::configured_channel(),
+    };
     emit_status("checking");
-    let updater = build_updater(&app, endpoint)?;
+    let updater = updater::build_updater(&app, endpoint, channel)?;
     let update = updater
         .check()
         .await
@@ -101,9 +130,22 @@ pub async fn download_and_install_update(
     commands_core::validate_update_version(env!("CARGO_PKG_VERSION"), &update.version)?;
 
     emit_status("downloading");
+    let mut downloaded: u64 = 0;
+    let mut last_emit: Option<std::time::Instant> = None;
     update
         .download_and_install(
-            |_, _| {},
+            |chunk_len, content_len| {
+                downloaded += chunk_len as u64;
+                let done = content_len.is_some_and(|total| downloaded >= total);
+                // Throttle so a fast connection doesn't flood the event bus
+                if done || last_emit.is_none_or(|t| t.elapsed() >= PROGRESS_EMIT_INTERVAL) {
+                    last_emit = Some(std::time::Instant::now());
+                    let _ = app.emit_app_event(&AppEvent::UpdateDownloadProgress {
+                        downloaded,
+                        total: content_len,
+                    });
+                }
+            },
             || info!("[updater] Download complete, installing..."),
         )
         .await
@@ -113,6 +155,27 @@ pub async fn download_and_install_update(
     Ok(())
 }
 
+#[tauri::command]
+pub fn set_auto_update_check(enabled: bool) {
+    updater::set_auto_update_check(enabled);
+}
+
+/// "stable" or "beta" for background checks and channel-less commands. Persisted.
+#[tauri::command]
+pub fn set_update_channel(channel: Option<String>) -> AppResult<()> {
+    updater::set_channel(channel)
+}
+
+#[tauri::command]
+pub fn skip_update_version(version: String) -> AppResult<()> {
+    updater::skip_version(version)
+}
+
+#[tauri::command]
+pub fn clear_skipped_version() {
+    updater::clear_skipped_version();
+}
+
 #[tauri::command]
 pub fn restart_app(app: tauri::AppHandle) {
     app.restart();
@@ -126,17 +189,68 @@ pub fn restart_app(app: tauri::AppHandle) {
 pub async fn open_oauth_in_browser(app: tauri::AppHandle, url: String) -> AppResult<()> {
     use tauri_plugin_opener::OpenerExt;
     commands_core::validate_oauth_url(&url)?;
+    crate::oauth::track_outgoing_url(&url);
     app.opener()
         .open_url(&url, None::<&str>)
         .map_err(|e| AppError::OAuth(format!("Failed to open browser: {}", e)))
 }
 
+/// Reveal the log directory in the file manager, for attaching logs to bug reports.
+#[tauri::command]
+pub fn open_logs_folder(app: tauri::AppHandle) -> AppResult<()> {
+    use tauri::Manager;
+    use tauri_plugin_opener::OpenerExt;
+
+    let dir = app.path().app_log_dir()?;
+    if !dir.is_dir() {
+        return Err(AppError::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Log directory does not exist yet: {}", dir.display()),
+        )));
+    }
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| AppError::Io(std::io::Error::other(e.to_string())))
+}
+
+#[tauri::command]
+pub fn begin_oauth(provider: String) -> AppResult<crate::oauth::OAuthBegin> {
+    crate::oauth::begin(provider)
+}
+
+#[tauri::command]
+pub fn complete_oauth(url: String) -> AppResult<crate::oauth::OAuthComplete> {
+    crate::oauth::complete(&url)
+}
+
+/// Fallback for machines where the `mywallpaper://` scheme is blocked: returns a
+/// `http://127.0.0.1:<port>/callback` redirect URI whose callback arrives as a
+/// `deep-link` event. The server closes after one callback or 5 minutes.
+#[tauri::command]
+pub fn start_oauth_loopback(app: tauri::AppHandle) -> AppResult<String> {
+    crate::oauth::start_loopback(app)
+}
+
 #[tauri::command]
 pub fn reload_window(app: tauri::AppHandle) -> AppResult<()> {
     app.emit_app_event(&AppEvent::ReloadApp)?;
     Ok(())
 }
 
+/// Bring the main window forward, e.g. after a `mywallpaper://app/settings` link.
+#[tauri::command]
+pub fn show_main_window(app: tauri::AppHandle) -> AppResult<()> {
+    use tauri::Manager;
+
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| AppError::WindowNotFound("main".into()))?;
+    window.show()?;
+    window.unminimize()?;
+    window.set_focus()?;
+    Ok(())
+}
+
 // ============================================================================
 // Media Commands
 // ============================================================================
@@ -146,6 +260,11 @@ pub fn get_media_info() -> AppResult<crate::media::MediaInfo> {
     crate::media::get_media_info()
 }
 
+#[tauri::command]
+pub fn list_media_apps() -> AppResult<Vec<crate::media::MediaApp>> {
+    crate::media::list_media_apps()
+}
+
 #[tauri::command]
 pub fn media_play_pause() -> AppResult<()> {
     crate::media::media_play_pause()
@@ -161,13 +280,299 @@ pub fn media_prev() -> AppResult<()> {
     crate::media::media_prev()
 }
 
+#[tauri::command]
+pub fn media_toggle_shuffle() -> AppResult<()> {
+    crate::media::media_toggle_shuffle()
+}
+
+#[tauri::command]
+pub fn media_set_repeat(mode: String) -> AppResult<()> {
+    crate::media::media_set_repeat(&mode)
+}
+
+#[tauri::command]
+pub fn media_get_volume() -> AppResult<f32> {
+    crate::media::media_get_volume()
+}
+
+#[tauri::command]
+pub fn media_set_volume(level: f32) -> AppResult<()> {
+    crate::media::media_set_volume(level)
+}
+
 // ============================================================================
 // Discord Commands
 // ============================================================================
 
 #[tauri::command]
-pub fn update_discord_presence(details: String, state: String) -> AppResult<()> {
-    crate::discord::update_presence(&details, &state)
+pub fn update_discord_presence(
+    details: String,
+    state: String,
+    large_image: Option<String>,
+    large_text: Option<String>,
+) -> AppResult<()> {
+    crate::discord::update_presence(
+        &details,
+        &state,
+        large_image.as_deref(),
+        large_text.as_deref(),
+    )
+}
+
+#[tauri::command]
+pub fn clear_discord_presence() -> AppResult<()> {
+    crate::discord::clear_presence()
+}
+
+#[tauri::command]
+pub fn disconnect_discord() {
+    crate::discord::disconnect();
+}
+
+#[tauri::command]
+pub fn set_discord_enabled(enabled: bool) {
+    crate::discord::set_enabled(enabled);
+}
+
+// ============================================================================
+// Tray Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn set_tray_stats(app: tauri::AppHandle, enabled: bool) {
+    crate::tray::set_stats_enabled(&app, enabled);
+}
+
+/// "active", "paused" or "error". Anything but "active" overrides the automatic
+/// dimming while the wallpaper is paused or hidden.
+#[tauri::command]
+pub fn set_tray_icon_state(app: tauri::AppHandle, state: String) -> AppResult<()> {
+    let state = commands_core::TrayIconState::parse(&state)?;
+    crate::tray::set_icon_state(&app, state);
+    Ok(())
+}
+
+// ============================================================================
+// Logging Commands
+// ============================================================================
+
+/// Change the log level without a restart, e.g. "debug" while chasing a bug.
+/// Persisted so it also covers startup on the next launch.
+#[tauri::command]
+pub fn set_log_level(level: String) -> AppResult<()> {
+    let filter = commands_core::parse_log_level(&level)?;
+    log::set_max_level(filter);
+    info!("[logging] Log level set to {}", filter);
+    crate::settings::update(|s| s.log_level = Some(filter.to_string().to_lowercase()));
+    Ok(())
+}
+
+/// Close the current log file and continue in a new one, so a bug report
+/// holds only the run that matters. Returns the new file's path.
+#[tauri::command]
+pub fn rotate_logs() -> AppResult<String> {
+    let path = crate::log_file::rotate()?;
+    info!("[logging] Started new log file");
+    Ok(path.to_string_lossy().into_owned())
+}
+
+// ============================================================================
+// Settings Commands
+// ============================================================================
+
+/// Read one persisted setting by its camelCase key.
+#[tauri::command]
+pub fn get_setting(key: String) -> AppResult<serde_json::Value> {
+    crate::settings::get_value(&key)
+}
+
+/// Persist one setting; emits `settings-changed` when the value differs.
+/// Keys with their own command are routed through it, so the value is
+/// validated and applied at runtime exactly as a direct call would.
+#[tauri::command]
+pub fn set_setting(app: tauri::AppHandle, key: String, value: serde_json::Value) -> AppResult<()> {
+    use crate::{shortcuts, window_layer};
+    use tauri::Manager;
+
+    fn arg<T: serde::de::DeserializeOwned>(key: &str, value: serde_json::Value) -> AppResult<T> {
+        serde_json::from_value(value)
+            .map_err(|e| AppError::Validation(format!("Invalid value for {}: {}", key, e)))
+    }
+
+    match key.as_str() {
+        "showDesktopIcons" => window_layer::set_desktop_icons_visible(app, arg(&key, value)?),
+        "trayStats" => {
+            set_tray_stats(app, arg(&key, value)?);
+            Ok(())
+        }
+        "autoUpdateCheck" => {
+            set_auto_update_check(arg(&key, value)?);
+            Ok(())
+        }
+        "skippedVersion" => match arg(&key, value)? {
+            Some(version) => skip_update_version(version),
+            None => {
+                clear_skipped_version();
+                Ok(())
+            }
+        },
+        "updateChannel" => set_update_channel(arg(&key, value)?),
+        "discordEnabled" => {
+            set_discord_enabled(arg(&key, value)?);
+            Ok(())
+        }
+        "idleTimeoutSecs" => {
+            set_idle_pause(app, arg(&key, value)?);
+            Ok(())
+        }
+        "playlist" => {
+            let playlist: crate::playlist::Playlist = arg(&key, value)?;
+            set_playlist(app, playlist.ids, playlist.interval_secs)
+        }
+        "watchdogIntervalSecs" => {
+            window_layer::set_watchdog_interval(arg(&key, value)?);
+            Ok(())
+        }
+        "pauseApps" => window_layer::set_pause_apps(arg(&key, value)?),
+        "layerMode" => window_layer::set_window_layer(arg(&key, value)?),
+        "backgroundColor" => {
+            let [r, g, b]: [u8; 3] = arg(&key, value)?;
+            let window = app
+                .get_webview_window("main")
+                .ok_or_else(|| AppError::Validation("Main window not found".into()))?;
+            window_layer::set_background_color(window, r, g, b);
+            Ok(())
+        }
+        "mouseForwardHz" => {
+            window_layer::set_mouse_forward_rate(arg(&key, value)?);
+            Ok(())
+        }
+        "wallpaperMonitor" => window_layer::set_wallpaper_monitor(app, arg(&key, value)?),
+        "monitorWallpapers" => Err(AppError::Validation(
+            "monitorWallpapers is set per monitor with set_monitor_wallpaper".into(),
+        )),
+        "respectTaskbar" => window_layer::set_respect_taskbar(arg(&key, value)?),
+        "targetFps" => set_target_fps(app, arg(&key, value)?),
+        "mouseleaveSuppression" => {
+            window_layer::set_mouseleave_suppression(arg(&key, value)?);
+            Ok(())
+        }
+        "iconInteraction" => {
+            window_layer::set_icon_interaction(arg(&key, value)?);
+            Ok(())
+        }
+        "layerShortcut" => match arg(&key, value)? {
+            Some(accelerator) => shortcuts::register_layer_shortcut(app, accelerator),
+            None => shortcuts::unregister_layer_shortcut(app),
+        },
+        "logLevel" => set_log_level(arg(&key, value)?),
+        "networkFilter" => set_network_filter(arg(&key, value)?),
+        "metricHistoryLength" => set_history_length(arg(&key, value)?),
+        // Only read at startup, where it is clamped
+        _ => crate::settings::set_value(&key, value),
+    }
+}
+
+// ============================================================================
+// Autostart Commands
+// ============================================================================
+
+/// Whether the app is registered to start at login. Reads the registry, so it
+/// reflects changes made in Task Manager.
+#[tauri::command]
+pub fn get_autostart_enabled(app: tauri::AppHandle) -> AppResult<bool> {
+    use tauri_plugin_autostart::ManagerExt;
+
+    app.autolaunch()
+        .is_enabled()
+        .map_err(|e| AppError::Autostart(e.to_string()))
+}
+
+/// Register or unregister start at login. The `--minimized` arg configured at
+/// plugin init is written again on every enable.
+#[tauri::command]
+pub fn set_autostart_enabled(app: tauri::AppHandle, enabled: bool) -> AppResult<()> {
+    use tauri_plugin_autostart::ManagerExt;
+
+    let autolaunch = app.autolaunch();
+    let result = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    result.map_err(|e| AppError::Autostart(e.to_string()))?;
+    info!(
+        "[autostart] {}",
+        if enabled { "Enabled" } else { "Disabled" }
+    );
+    Ok(())
+}
+
+// ============================================================================
+// Idle Commands
+// ============================================================================
+
+/// Pause the wallpaper after `timeout_secs` without input; `None` disables.
+#[tauri::command]
+pub fn set_idle_pause(app: tauri::AppHandle, timeout_secs: Option<u64>) {
+    crate::idle::set_timeout(&app, timeout_secs);
+}
+
+// ============================================================================
+// Performance Hint Commands
+// ============================================================================
+
+/// Initial state for the `performance-hint` event.
+#[tauri::command]
+pub fn get_performance_hint() -> crate::performance::PerformanceHint {
+    crate::performance::get()
+}
+
+/// Light/dark mode and accent color; changes emit `theme-changed`.
+#[tauri::command]
+pub fn get_system_theme() -> crate::theme::SystemTheme {
+    crate::theme::get()
+}
+
+// ============================================================================
+// Frame Rate Commands
+// ============================================================================
+
+/// Current animation frame-rate cap (0 while hidden); changes emit
+/// `target-fps-changed`.
+#[tauri::command]
+pub fn get_target_fps() -> u32 {
+    crate::frame_rate::get()
+}
+
+/// Base frame rate (1-240) before the battery and visibility caps. Persisted.
+#[tauri::command]
+pub fn set_target_fps(app: tauri::AppHandle, fps: u32) -> AppResult<()> {
+    crate::frame_rate::set_base(&app, fps)
+}
+
+// ============================================================================
+// Playlist Commands
+// ============================================================================
+
+#[tauri::command]
+pub fn get_playlist() -> crate::playlist::Playlist {
+    crate::playlist::get()
+}
+
+#[tauri::command]
+pub fn set_playlist(app: tauri::AppHandle, ids: Vec<String>, interval_secs: u64) -> AppResult<()> {
+    crate::playlist::set(&app, ids, interval_secs)
+}
+
+#[tauri::command]
+pub fn next_wallpaper(app: tauri::AppHandle) -> Option<String> {
+    crate::playlist::advance(&app, 1)
+}
+
+#[tauri::command]
+pub fn prev_wallpaper(app: tauri::AppHandle) -> Option<String> {
+    crate::playlist::advance(&app, -1)
 }
 
 #[cfg(test)]
@@ -1,57 +1,222 @@
 //! Discord Rich Presence — shows "Using MyWallpaper" in Discord.
-//! Fails silently if Discord is not running.
+//! Fails silently if Discord is not running, and reconnects in the background
+//! with exponential backoff once Discord comes (back) up.
 
 use crate::error::AppResult;
 use discord_rich_presence::{activity, DiscordIpc, DiscordIpcClient};
-use log::{info, warn};
+use log::{debug, info, warn};
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
 use std::sync::Mutex;
+use std::time::Duration;
 
-// MyWallpaper Discord application ID (create at https://discord.com/developers/applications)
-const DISCORD_APP_ID: &str = "1307092087033782272";
+// MyWallpaper Discord application ID (create at https://discord.com/developers/applications).
+// It and the default assets are overridable at build time so forks and white-label
+// builds can use their own application (e.g. `MW_DISCORD_APP_ID=123 cargo build`).
+const DISCORD_APP_ID: &str = match option_env!("MW_DISCORD_APP_ID") {
+    Some(id) => id,
+    None => "1307092087033782272",
+};
+const DEFAULT_LARGE_IMAGE: &str = match option_env!("MW_DISCORD_LARGE_IMAGE") {
+    Some(key) => key,
+    None => "logo",
+};
+const DEFAULT_LARGE_TEXT: &str = match option_env!("MW_DISCORD_LARGE_TEXT") {
+    Some(text) => text,
+    None => "MyWallpaper Desktop",
+};
+
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_secs(5);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(5 * 60);
 
 static CLIENT: Mutex<Option<DiscordIpcClient>> = Mutex::new(None);
+/// App launch time (unix seconds) — kept across updates so elapsed time never resets.
+static START_TIMESTAMP: AtomicI64 = AtomicI64::new(0);
+/// Last requested activity, re-applied after a reconnect.
+static LAST_PRESENCE: Mutex<Option<Presence>> = Mutex::new(None);
+/// Ensures a single reconnect loop at a time.
+static RECONNECTING: AtomicBool = AtomicBool::new(false);
+/// Cleared by `disconnect` so the reconnect loop stops instead of reconnecting.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone)]
+struct Presence {
+    details: String,
+    state: String,
+    large_image: String,
+    large_text: String,
+}
+
+impl Presence {
+    fn initial() -> Self {
+        Self {
+            details: "Using MyWallpaper".into(),
+            state: "Animated Wallpaper".into(),
+            large_image: DEFAULT_LARGE_IMAGE.into(),
+            large_text: DEFAULT_LARGE_TEXT.into(),
+        }
+    }
+
+    fn activity(&self) -> activity::Activity<'_> {
+        activity::Activity::new()
+            .state(&self.state)
+            .details(&self.details)
+            .timestamps(activity::Timestamps::new().start(START_TIMESTAMP.load(Ordering::Relaxed)))
+            .assets(
+                activity::Assets::new()
+                    .large_image(&self.large_image)
+                    .large_text(&self.large_text),
+            )
+    }
+}
 
 /// Connect to Discord RPC. Fails silently if Discord is not running.
+/// Skipped entirely when the user disabled Rich Presence.
 pub fn init() {
-    std::thread::spawn(|| match DiscordIpcClient::new(DISCORD_APP_ID) {
-        Ok(mut client) => {
-            if client.connect().is_ok() {
-                let activity = activity::Activity::new()
-                    .state("Animated Wallpaper")
-                    .details("Using MyWallpaper")
-                    .assets(
-                        activity::Assets::new()
-                            .large_image("logo")
-                            .large_text("MyWallpaper Desktop"),
-                    );
-                let _ = client.set_activity(activity);
-                *CLIENT.lock().unwrap() = Some(client);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    START_TIMESTAMP.store(now, Ordering::Relaxed);
+
+    if !crate::settings::get().discord_enabled {
+        info!("[discord] Rich Presence disabled by user");
+        return;
+    }
+    *LAST_PRESENCE.lock().unwrap() = Some(Presence::initial());
+
+    ENABLED.store(true, Ordering::SeqCst);
+    spawn_reconnect();
+}
+
+/// Try to connect once and apply the last known activity.
+fn try_connect() -> bool {
+    let mut client = match DiscordIpcClient::new(DISCORD_APP_ID) {
+        Ok(c) => c,
+        Err(e) => {
+            warn!("[discord] Failed to create IPC client: {}", e);
+            return false;
+        }
+    };
+    if client.connect().is_err() {
+        return false;
+    }
+    // A cleared presence stays cleared after reconnecting
+    let presence = LAST_PRESENCE.lock().unwrap().clone();
+    if let Some(presence) = presence {
+        if client.set_activity(presence.activity()).is_err() {
+            let _ = client.close();
+            return false;
+        }
+    }
+    *CLIENT.lock().unwrap() = Some(client);
+    true
+}
+
+/// Background reconnect loop with exponential backoff. Never blocks the caller.
+fn spawn_reconnect() {
+    if RECONNECTING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        let mut delay = RECONNECT_INITIAL_DELAY;
+        let mut first = true;
+        loop {
+            if !ENABLED.load(Ordering::SeqCst) {
+                break;
+            }
+            if try_connect() {
                 info!("[discord] Rich Presence connected");
+                // Disabled while connecting: don't leave a live client behind
+                if !ENABLED.load(Ordering::SeqCst) {
+                    close_client();
+                }
+                break;
+            }
+            if first {
+                warn!("[discord] Discord not running, will retry in the background");
+                first = false;
             } else {
-                warn!("[discord] Discord not running, skipping Rich Presence");
+                debug!(
+                    "[discord] Reconnect failed, retrying in {}s",
+                    delay.as_secs()
+                );
             }
+            std::thread::sleep(delay);
+            delay = (delay * 2).min(RECONNECT_MAX_DELAY);
         }
-        Err(e) => {
-            warn!("[discord] Failed to create IPC client: {}", e);
-        }
+        RECONNECTING.store(false, Ordering::SeqCst);
     });
 }
 
 /// Update the Discord Rich Presence activity.
-pub fn update_presence(details: &str, state: &str) -> AppResult<()> {
+/// `large_image`/`large_text` default to the app logo when not provided.
+/// If Discord is unreachable, the activity is kept and applied on reconnect.
+pub fn update_presence(
+    details: &str,
+    state: &str,
+    large_image: Option<&str>,
+    large_text: Option<&str>,
+) -> AppResult<()> {
+    let presence = Presence {
+        details: details.to_string(),
+        state: state.to_string(),
+        large_image: large_image.unwrap_or(DEFAULT_LARGE_IMAGE).to_string(),
+        large_text: large_text.unwrap_or(DEFAULT_LARGE_TEXT).to_string(),
+    };
+    *LAST_PRESENCE.lock().unwrap() = Some(presence.clone());
+
+    let mut guard = CLIENT.lock().unwrap();
+    if let Some(ref mut client) = *guard {
+        if let Err(e) = client.set_activity(presence.activity()) {
+            // Discord was closed: drop the dead client and reconnect in the background
+            warn!("[discord] Lost connection ({}), reconnecting", e);
+            *guard = None;
+            drop(guard);
+            spawn_reconnect();
+        }
+    }
+    Ok(())
+}
+
+/// Clear the current activity but keep the IPC connection open.
+pub fn clear_presence() -> AppResult<()> {
+    *LAST_PRESENCE.lock().unwrap() = None;
     let mut guard = CLIENT.lock().unwrap();
     if let Some(ref mut client) = *guard {
-        let activity = activity::Activity::new()
-            .state(state)
-            .details(details)
-            .assets(
-                activity::Assets::new()
-                    .large_image("logo")
-                    .large_text("MyWallpaper Desktop"),
-            );
         client
-            .set_activity(activity)
+            .clear_activity()
             .map_err(|e| crate::error::AppError::Io(std::io::Error::other(e.to_string())))?;
     }
     Ok(())
 }
+
+fn close_client() {
+    if let Some(mut client) = CLIENT.lock().unwrap().take() {
+        let _ = client.clear_activity();
+        let _ = client.close();
+    }
+}
+
+/// Close the IPC client and stop any reconnect attempts.
+pub fn disconnect() {
+    ENABLED.store(false, Ordering::SeqCst);
+    close_client();
+    info!("[discord] Rich Presence disconnected");
+}
+
+/// Persist the Rich Presence preference and connect/disconnect accordingly.
+pub fn set_enabled(enabled: bool) {
+    crate::settings::update(|s| s.discord_enabled = enabled);
+    if enabled {
+        if !ENABLED.swap(true, Ordering::SeqCst) {
+            let mut last = LAST_PRESENCE.lock().unwrap();
+            if last.is_none() {
+                *last = Some(Presence::initial());
+            }
+            drop(last);
+            spawn_reconnect();
+        }
+    } else {
+        disconnect();
+    }
+}
@@ -17,16 +17,44 @@ pub enum AppError {
     WindowNotFound(String),
     #[error("Media: {0}")]
     Media(String),
+    #[error("Autostart: {0}")]
+    Autostart(String),
     #[error(transparent)]
     Tauri(#[from] tauri::Error),
     #[error("IO: {0}")]
     Io(#[from] std::io::Error),
 }
 
-// Serialize as string for backwards compatibility — frontend already handles string errors.
+impl AppError {
+    /// Stable machine-readable code — the frontend branches on this, never on `message`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::WindowLayer(_) => "WINDOW_LAYER",
+            Self::Updater(_) => "UPDATER",
+            Self::Validation(_) => "VALIDATION",
+            Self::SystemMonitor(_) => "SYSTEM_MONITOR",
+            Self::OAuth(_) => "OAUTH",
+            Self::WindowNotFound(_) => "WINDOW_NOT_FOUND",
+            Self::Media(_) => "MEDIA",
+            Self::Autostart(_) => "AUTOSTART",
+            Self::Tauri(_) => "TAURI",
+            Self::Io(_) => "IO",
+        }
+    }
+}
+
+// Serialized as `{ code, message }`. The `legacy-string-errors` feature keeps the
+// old bare-string shape for frontends that haven't migrated yet.
 impl Serialize for AppError {
     fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
-        s.serialize_str(&self.to_string())
+        if cfg!(feature = "legacy-string-errors") {
+            return s.serialize_str(&self.to_string());
+        }
+        use serde::ser::SerializeStruct;
+        let mut st = s.serialize_struct("AppError", 2)?;
+        st.serialize_field("code", self.code())?;
+        st.serialize_field("message", &self.to_string())?;
+        st.end()
     }
 }
 
@@ -3,25 +3,86 @@ use serde::Serialize;
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", content = "data")]
 pub enum AppEvent {
-    WallpaperVisibility { visible: bool },
-    UpdateProgress { status: String },
+    WallpaperVisibility {
+        visible: bool,
+    },
+    WallpaperChanged {
+        id: String,
+    },
+    FilesDropped {
+        paths: Vec<String>,
+    },
+    UpdateProgress {
+        status: String,
+    },
+    UpdateDownloadProgress {
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    UpdateAvailable(crate::commands_core::UpdateInfo),
+    UpdateCheckResult {
+        status: String,
+        version: Option<String>,
+    },
     SystemDataUpdate(Box<crate::system_monitor::SystemData>),
-    DeepLink { url: String },
+    DeepLink(crate::commands_core::DeepLink),
     ReloadApp,
-    SessionStateChanged { active: bool },
+    SessionStateChanged {
+        active: bool,
+    },
     WebViewReloaded,
+    MonitorsChanged {
+        count: u32,
+        monitors: Vec<crate::window_layer::MonitorRect>,
+    },
+    PerformanceHint(crate::performance::PerformanceHint),
+    ThemeChanged(crate::theme::SystemTheme),
+    SettingsChanged {
+        keys: Vec<String>,
+    },
+    MonitorDpiChanged {
+        monitors: Vec<crate::window_layer::MonitorDpi>,
+    },
+    SetupProgress {
+        phase: String,
+    },
+    PowerStateChanged {
+        charging: bool,
+        /// `None` when Windows doesn't know the charge level.
+        level: Option<f32>,
+    },
+    SecondInstanceLaunched {
+        args: Vec<String>,
+    },
+    TargetFpsChanged {
+        fps: u32,
+    },
 }
 
 impl AppEvent {
     pub fn event_name(&self) -> &'static str {
         match self {
             Self::WallpaperVisibility { .. } => "wallpaper-visibility",
+            Self::WallpaperChanged { .. } => "wallpaper-changed",
+            Self::FilesDropped { .. } => "files-dropped",
             Self::UpdateProgress { .. } => "update-progress",
+            Self::UpdateDownloadProgress { .. } => "update-download-progress",
+            Self::UpdateAvailable(_) => "update-available",
+            Self::UpdateCheckResult { .. } => "update-check-result",
             Self::SystemDataUpdate(_) => "system-data-update",
-            Self::DeepLink { .. } => "deep-link",
+            Self::DeepLink(_) => "deep-link",
             Self::ReloadApp => "reload-app",
             Self::SessionStateChanged { .. } => "session-state-changed",
             Self::WebViewReloaded => "webview-reloaded",
+            Self::MonitorsChanged { .. } => "monitors-changed",
+            Self::PerformanceHint(_) => "performance-hint",
+            Self::ThemeChanged(_) => "theme-changed",
+            Self::SettingsChanged { .. } => "settings-changed",
+            Self::MonitorDpiChanged { .. } => "monitor-dpi-changed",
+            Self::SetupProgress { .. } => "setup-progress",
+            Self::PowerStateChanged { .. } => "power-state-changed",
+            Self::SecondInstanceLaunched { .. } => "second-instance-launched",
+            Self::TargetFpsChanged { .. } => "target-fps-changed",
         }
     }
 }
@@ -0,0 +1,70 @@
+//! Target frame rate for the frontend's animation loop.
+//!
+//! The user picks a base rate; the effective cap drops to `BATTERY_FPS_CAP`
+//! on battery or battery saver and to 0 while the wallpaper is hidden (tray
+//! pause, idle, fullscreen app). Every change emits `target-fps-changed`.
+
+use crate::commands_core::{effective_fps, validate_target_fps, DEFAULT_TARGET_FPS};
+use crate::error::AppResult;
+use crate::events::{AppEvent, EmitAppEvent};
+use log::{info, warn};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// The user's preference, before power and visibility caps.
+static BASE_FPS: AtomicU32 = AtomicU32::new(DEFAULT_TARGET_FPS);
+/// Last cap sent to the frontend.
+static EFFECTIVE_FPS: AtomicU32 = AtomicU32::new(DEFAULT_TARGET_FPS);
+
+/// Restore the persisted base rate and emit the initial cap if it differs
+/// from the default.
+pub fn init(app: &tauri::AppHandle) {
+    let base = crate::settings::get().target_fps;
+    match validate_target_fps(base) {
+        Ok(()) => BASE_FPS.store(base, Ordering::SeqCst),
+        Err(e) => warn!("[frame_rate] Ignoring saved target FPS: {}", e),
+    }
+    refresh(app);
+}
+
+/// The cap the animation loop should honor right now.
+pub fn get() -> u32 {
+    EFFECTIVE_FPS.load(Ordering::SeqCst)
+}
+
+/// Change the base rate (1-240) and persist it.
+pub fn set_base(app: &tauri::AppHandle, fps: u32) -> AppResult<()> {
+    validate_target_fps(fps)?;
+    BASE_FPS.store(fps, Ordering::SeqCst);
+    crate::settings::update(|s| s.target_fps = fps);
+    info!("[frame_rate] Base target: {} FPS", fps);
+    refresh(app);
+    Ok(())
+}
+
+/// Recompute the cap after the base, visibility or power state changed.
+pub fn refresh(app: &tauri::AppHandle) {
+    let on_battery = crate::performance::get().low_power || on_battery_power();
+    let fps = effective_fps(
+        BASE_FPS.load(Ordering::SeqCst),
+        crate::tray::wallpaper_visible(),
+        on_battery,
+    );
+    if EFFECTIVE_FPS.swap(fps, Ordering::SeqCst) != fps {
+        info!("[frame_rate] Target: {} FPS", fps);
+        let _ = app.emit_app_event(&AppEvent::TargetFpsChanged { fps });
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn on_battery_power() -> bool {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    // ACLineStatus: 0 offline, 1 online, 255 unknown (desktops without a battery)
+    unsafe { GetSystemPowerStatus(&mut status) }.is_ok() && status.ACLineStatus == 0
+}
+
+#[cfg(not(target_os = "windows"))]
+fn on_battery_power() -> bool {
+    false
+}
@@ -0,0 +1,92 @@
+//! Idle watcher — hides the wallpaper after a period without user input.
+//!
+//! Idle is one of several reasons to hide the wallpaper; the combined
+//! visibility is emitted through `tray::emit_visibility`, so leaving idle
+//! never resumes a wallpaper the user paused from the tray.
+
+use log::info;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Idle timeout in seconds; 0 disables the feature.
+static IDLE_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(0);
+static IS_IDLE: AtomicBool = AtomicBool::new(false);
+static WATCHER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Short enough that input resumes the wallpaper without a noticeable lag.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Restore the persisted timeout and start the watcher if it is enabled.
+pub fn init(app: &tauri::AppHandle) {
+    if let Some(secs) = crate::settings::get().idle_timeout_secs {
+        IDLE_TIMEOUT_SECS.store(secs, Ordering::SeqCst);
+        start_watcher(app.clone());
+    }
+}
+
+/// Whether the user is currently considered idle.
+pub fn is_idle() -> bool {
+    IS_IDLE.load(Ordering::SeqCst)
+}
+
+/// Set the idle timeout (`None` or 0 disables it) and persist the choice.
+pub fn set_timeout(app: &tauri::AppHandle, timeout_secs: Option<u64>) {
+    let timeout_secs = timeout_secs.filter(|&s| s > 0);
+    IDLE_TIMEOUT_SECS.store(timeout_secs.unwrap_or(0), Ordering::SeqCst);
+    crate::settings::update(|s| s.idle_timeout_secs = timeout_secs);
+    match timeout_secs {
+        Some(secs) => {
+            info!("[idle] Pause after {}s without input", secs);
+            start_watcher(app.clone());
+        }
+        None => {
+            info!("[idle] Idle pause disabled");
+            if IS_IDLE.swap(false, Ordering::SeqCst) {
+                crate::tray::emit_visibility(app);
+            }
+        }
+    }
+}
+
+/// Poll the last input time. The thread lives for the app lifetime once
+/// started and simply idles while the feature is disabled.
+fn start_watcher(app: tauri::AppHandle) {
+    if WATCHER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(move || loop {
+        let timeout = IDLE_TIMEOUT_SECS.load(Ordering::SeqCst);
+        let idle =
+            timeout > 0 && idle_duration().is_some_and(|d| d >= Duration::from_secs(timeout));
+        if IS_IDLE.swap(idle, Ordering::SeqCst) != idle {
+            info!("[idle] User {}", if idle { "idle" } else { "active" });
+            crate::tray::emit_visibility(&app);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    });
+}
+
+/// Time since the last keyboard/mouse input in this session.
+#[cfg(target_os = "windows")]
+fn idle_duration() -> Option<Duration> {
+    use windows::Win32::System::SystemInformation::GetTickCount;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return None;
+        }
+        // Both are 32-bit tick counts; wrapping_sub survives the 49.7-day rollover
+        let elapsed_ms = GetTickCount().wrapping_sub(info.dwTime);
+        Some(Duration::from_millis(elapsed_ms as u64))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn idle_duration() -> Option<Duration> {
+    None
+}
@@ -7,9 +7,19 @@ mod commands_core;
 mod discord;
 pub mod error;
 pub mod events;
+mod frame_rate;
+mod idle;
+mod log_file;
 mod media;
+mod oauth;
+mod performance;
+mod playlist;
+mod settings;
+mod shortcuts;
 mod system_monitor;
+mod theme;
 mod tray;
+mod updater;
 mod window_layer;
 
 use log::{error, info, warn};
@@ -18,12 +28,13 @@ use std::sync::{Arc, LazyLock};
 
 static MW_INIT_SCRIPT: LazyLock<String> = LazyLock::new(|| {
     format!(
-        r#"window.__MW_INIT__ = {{ isTauri: true, platform: "{}", arch: "{}", appVersion: "{}", tauriVersion: "{}", debug: {} }};"#,
+        r#"window.__MW_INIT__ = {{ isTauri: true, platform: "{}", arch: "{}", appVersion: "{}", tauriVersion: "{}", debug: {}, launchedMinimized: {} }};"#,
         std::env::consts::OS,
         std::env::consts::ARCH,
         env!("CARGO_PKG_VERSION"),
         tauri::VERSION,
         cfg!(debug_assertions),
+        commands_core::launched_minimized(std::env::args_os()),
     )
 });
 
@@ -64,28 +75,60 @@ pub fn main() {
         std::env::consts::OS,
         std::env::consts::ARCH
     );
+    window_layer::install_crash_guard();
     start_with_tauri_webview();
 }
 
-fn start_with_tauri_webview() {
+/// Validate a deep link and forward it to the frontend; false if dropped.
+/// OAuth callbacks are dropped unless their `state` matches an in-flight flow.
+fn deliver_deep_link(app: &tauri::AppHandle, raw: &str) -> bool {
     use events::{AppEvent, EmitAppEvent};
+
+    let Some(link) = commands_core::parse_deep_link(raw) else {
+        // Single-instance argv also carries the exe path and flags; only
+        // rejected links of our own scheme are worth a warning
+        if raw.to_ascii_lowercase().starts_with("mywallpaper:") {
+            warn!("[deep-link] Dropped invalid or disallowed link");
+        }
+        return false;
+    };
+    if link.action == "callback" && !oauth::accept_callback(&link.url) {
+        warn!("[deep-link] Dropped OAuth callback with invalid state");
+        return false;
+    }
+    // Log only the route: callback URLs carry OAuth codes
+    info!("[deep-link] Received \"{}\" link", link.action);
+    let _ = app.emit_app_event(&AppEvent::DeepLink(link));
+    true
+}
+
+/// Apply the persisted log level, falling back to the build-time default.
+fn apply_log_level(saved: Option<&str>) {
+    let level = match saved.map(commands_core::parse_log_level) {
+        Some(Ok(level)) => level,
+        Some(Err(e)) => {
+            warn!("[logging] Ignoring saved log level: {}", e);
+            commands_core::default_log_level()
+        }
+        None => commands_core::default_log_level(),
+    };
+    log::set_max_level(level);
+}
+
+fn start_with_tauri_webview() {
     use tauri::{webview::PageLoadEvent, Listener, Manager};
 
     let app = tauri::Builder::default()
         .plugin(
             tauri_plugin_log::Builder::new()
-                .level(if cfg!(debug_assertions) {
-                    log::LevelFilter::Debug
-                } else {
-                    log::LevelFilter::Info
-                })
+                // Let everything through the dispatcher; the effective level is
+                // `log::max_level`, set in setup and by `set_log_level`
+                .level(log::LevelFilter::Trace)
                 .clear_targets()
                 .target(tauri_plugin_log::Target::new(
                     tauri_plugin_log::TargetKind::Webview,
                 ))
-                .target(tauri_plugin_log::Target::new(
-                    tauri_plugin_log::TargetKind::LogDir { file_name: None },
-                ))
+                .target(log_file::target())
                 .target(tauri_plugin_log::Target::new(
                     tauri_plugin_log::TargetKind::Stdout,
                 ))
@@ -95,23 +138,29 @@ fn start_with_tauri_webview() {
         // MacosLauncher is required by the API but inert on Windows
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
-            Some(vec!["--minimized"]),
+            Some(vec![commands_core::MINIMIZED_FLAG]),
         ))
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_deep_link::init())
+        .plugin(shortcuts::plugin())
         .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
-            args.into_iter()
-                .filter_map(|a| commands_core::validate_deep_link(&a))
-                .for_each(|url| {
-                    let _ = app.emit_app_event(&AppEvent::DeepLink { url });
-                });
+            use events::{AppEvent, EmitAppEvent};
+
+            // Told even without a deep link, so a plain relaunch isn't a no-op
+            let flags = commands_core::known_launch_flags(args.iter().cloned());
+            info!("[main] Second instance launched (flags: {:?})", flags);
+            let _ = app.emit_app_event(&AppEvent::SecondInstanceLaunched { args: flags });
+            for a in args {
+                deliver_deep_link(app, &a);
+            }
         }))
         .on_page_load(|webview, payload| {
             if payload.event() == PageLoadEvent::Started {
                 let _ = webview.eval(&*MW_INIT_SCRIPT);
             }
             if payload.event() == PageLoadEvent::Finished {
+                window_layer::on_page_finished(webview.app_handle());
                 // Heartbeat: frontend pings every 5s so backend can detect unresponsive WebView
                 let _ = webview.eval(
                     r#"
@@ -129,6 +178,10 @@ fn start_with_tauri_webview() {
         })
         .setup(|app| {
             let handle = app.handle().clone();
+            log_file::init(&handle);
+            settings::init(&handle);
+            apply_log_level(settings::get().log_level.as_deref());
+            theme::init();
             if let Err(e) = tray::setup_tray(&handle) {
                 error!("[setup] Failed to setup system tray: {}", e);
             }
@@ -136,22 +189,45 @@ fn start_with_tauri_webview() {
             let deep_link_handle = handle.clone();
             app.listen("deep-link://new-url", move |event| {
                 if let Ok(urls) = serde_json::from_str::<Vec<String>>(event.payload()) {
-                    urls.into_iter()
-                        .filter_map(|u| commands_core::validate_deep_link(&u))
-                        .for_each(|url| {
-                            let _ = deep_link_handle.emit_app_event(&AppEvent::DeepLink { url });
-                        });
+                    for u in &urls {
+                        deliver_deep_link(&deep_link_handle, u);
+                    }
                 }
             });
 
             if let Some(window) = app.get_webview_window("main") {
-                let _ = window.set_background_color(Some(tauri::webview::Color(0, 0, 0, 255)));
+                window_layer::apply_background_color(&window, settings::get().background_color);
+                window_layer::apply_watchdog_interval(settings::get().watchdog_interval_secs);
+                window_layer::load_pause_apps(&settings::get().pause_apps);
+                window_layer::apply_mouse_forward_rate(settings::get().mouse_forward_hz);
+                window_layer::apply_mouseleave_suppression(settings::get().mouseleave_suppression);
+                window_layer::apply_icon_interaction(settings::get().icon_interaction);
+                window_layer::apply_wallpaper_monitor(settings::get().wallpaper_monitor);
+                window_layer::apply_respect_taskbar(settings::get().respect_taskbar);
                 window_layer::setup_desktop_window(&window);
+                window_layer::restore_layer_mode();
+                if !settings::get().show_desktop_icons {
+                    window_layer::apply_desktop_icons_visible(false);
+                }
                 let _ = window.show();
             }
 
+            match commands_core::NetworkFilter::parse(&settings::get().network_filter) {
+                Ok(filter) => system_monitor::set_network_filter(filter),
+                Err(e) => warn!("[setup] Ignoring saved network filter: {}", e),
+            }
+            let history_length = settings::get().metric_history_length;
+            if commands_core::validate_history_length(history_length).is_ok() {
+                system_monitor::set_history_length(history_length);
+            }
             system_monitor::start_monitor(handle.clone(), 3);
+            updater::start_update_watcher(handle.clone(), 6);
             discord::init();
+            idle::init(&handle);
+            playlist::init(&handle);
+            performance::init(&handle);
+            frame_rate::init(&handle);
+            shortcuts::init(&handle);
 
             // WebView heartbeat watchdog — auto-reload if frontend stops responding
             fn now_secs() -> u64 {
@@ -192,17 +268,89 @@ fn start_with_tauri_webview() {
             commands::get_system_info,
             commands::get_system_data,
             commands::subscribe_system_data,
+            commands::refresh_system_data_now,
+            commands::get_system_pressure,
+            commands::set_network_filter,
+            commands::get_metric_history,
+            commands::set_history_length,
+            commands::stop_system_monitor,
             commands::check_for_updates,
             commands::download_and_install_update,
+            commands::set_auto_update_check,
+            commands::set_update_channel,
+            commands::skip_update_version,
+            commands::clear_skipped_version,
             commands::restart_app,
+            commands::open_logs_folder,
             commands::open_oauth_in_browser,
+            commands::begin_oauth,
+            commands::complete_oauth,
+            commands::start_oauth_loopback,
             commands::reload_window,
+            commands::show_main_window,
+            window_layer::hard_reload_webview,
+            window_layer::reinit_window_layer,
             commands::get_media_info,
+            commands::list_media_apps,
             commands::media_play_pause,
             commands::media_next,
             commands::media_prev,
+            commands::media_toggle_shuffle,
+            commands::media_set_repeat,
+            commands::media_get_volume,
+            commands::media_set_volume,
             commands::update_discord_presence,
+            commands::clear_discord_presence,
+            commands::disconnect_discord,
+            commands::set_discord_enabled,
+            commands::set_tray_stats,
+            commands::set_tray_icon_state,
+            commands::set_idle_pause,
+            commands::get_performance_hint,
+            commands::get_system_theme,
+            commands::get_target_fps,
+            commands::set_target_fps,
+            commands::set_log_level,
+            commands::rotate_logs,
+            commands::get_setting,
+            commands::set_setting,
+            commands::get_autostart_enabled,
+            commands::set_autostart_enabled,
+            commands::get_playlist,
+            commands::set_playlist,
+            commands::next_wallpaper,
+            commands::prev_wallpaper,
             window_layer::set_desktop_icons_visible,
+            window_layer::set_background_color,
+            window_layer::get_layer_status,
+            window_layer::get_setup_phase,
+            window_layer::diagnose_environment,
+            window_layer::get_webview_info,
+            window_layer::get_monitors,
+            window_layer::get_monitor_dpi,
+            window_layer::get_virtual_desktop_bounds,
+            window_layer::set_wallpaper_monitor,
+            window_layer::set_respect_taskbar,
+            window_layer::get_monitor_assignments,
+            window_layer::set_monitor_wallpaper,
+            window_layer::get_available_layer_modes,
+            window_layer::get_window_layer,
+            window_layer::set_window_layer,
+            window_layer::toggle_window_layer,
+            window_layer::preview_wallpaper,
+            shortcuts::register_layer_shortcut,
+            shortcuts::unregister_layer_shortcut,
+            shortcuts::get_registered_shortcuts,
+            window_layer::set_pause_apps,
+            window_layer::capture_wallpaper,
+            window_layer::get_wallpaper_dominant_color,
+            window_layer::set_watchdog_interval,
+            window_layer::set_mouse_forward_rate,
+            window_layer::set_hook_exclusion_rects,
+            window_layer::set_mouseleave_suppression,
+            window_layer::set_icon_interaction,
+            window_layer::set_hook_stats_enabled,
+            window_layer::get_hook_stats,
         ])
         .build(tauri::generate_context!())
         .expect("Error while building MyWallpaper Desktop");
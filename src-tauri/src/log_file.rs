@@ -0,0 +1,157 @@
+//! The log file target.
+//!
+//! Owned here rather than by the log plugin's `LogDir` target so `rotate_logs`
+//! can close the current file and start a fresh one mid-session. Every file is
+//! named after the time it was opened; opening one prunes all but the newest
+//! `MAX_FILES`.
+
+use crate::error::{AppError, AppResult};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// A file past this size is rotated on the next write.
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// Files kept in the log dir, the current one included; older ones are deleted.
+const MAX_FILES: usize = 10;
+/// Lines kept from before `init` knows the log dir, flushed into the first file.
+const MAX_EARLY_LINES: usize = 1000;
+
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+}
+
+enum State {
+    /// Plugin is logging but setup hasn't resolved the log dir yet
+    Pending(Vec<String>),
+    Open(LogFile),
+    /// The log dir couldn't be created; file logging is off
+    Disabled,
+}
+
+static STATE: Mutex<State> = Mutex::new(State::Pending(Vec::new()));
+
+/// Log plugin target writing to the current file.
+pub fn target() -> tauri_plugin_log::Target {
+    let output = fern::Output::call(|record| write_line(&format!("{}\n", record.args())));
+    tauri_plugin_log::Target::new(tauri_plugin_log::TargetKind::Dispatch(
+        fern::Dispatch::new().chain(output),
+    ))
+}
+
+/// Open the first file in the app log dir. Call once during setup.
+pub fn init(app: &tauri::AppHandle) {
+    use tauri::Manager;
+
+    let opened = app
+        .path()
+        .app_log_dir()
+        .map_err(AppError::from)
+        .and_then(|dir| LogFile::create(&dir));
+    let mut state = lock();
+    let early = match &mut *state {
+        State::Pending(lines) => std::mem::take(lines),
+        _ => return,
+    };
+    match opened {
+        Ok(mut log) => {
+            for line in &early {
+                log.write(line);
+            }
+            *state = State::Open(log);
+        }
+        Err(e) => {
+            // Logging from here would re-enter the logger while we hold its lock
+            eprintln!("[log_file] File logging disabled: {}", e);
+            *state = State::Disabled;
+        }
+    }
+}
+
+/// Close the current file and continue in a new one; returns its path.
+pub fn rotate() -> AppResult<PathBuf> {
+    let mut state = lock();
+    let State::Open(log) = &mut *state else {
+        return Err(AppError::Io(std::io::Error::other(
+            "File logging is not active",
+        )));
+    };
+    let dir = log.path.parent().map(Path::to_path_buf).unwrap_or_default();
+    *log = LogFile::create(&dir)?;
+    Ok(log.path.clone())
+}
+
+fn write_line(line: &str) {
+    let mut state = lock();
+    match &mut *state {
+        State::Pending(lines) => {
+            if lines.len() < MAX_EARLY_LINES {
+                lines.push(line.to_string());
+            }
+        }
+        State::Open(log) => {
+            if log.written >= MAX_FILE_BYTES {
+                let dir = log.path.parent().map(Path::to_path_buf).unwrap_or_default();
+                if let Ok(next) = LogFile::create(&dir) {
+                    *log = next;
+                }
+            }
+            log.write(line);
+        }
+        State::Disabled => {}
+    }
+}
+
+/// Delete all but the newest `MAX_FILES` logs. Best effort: runs under the
+/// logger's lock, so failures can't be logged.
+fn prune(dir: &Path) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let names = entries
+        .filter_map(|e| e.ok()?.file_name().into_string().ok())
+        .collect();
+    for name in crate::commands_core::log_files_to_prune(names, MAX_FILES) {
+        let _ = std::fs::remove_file(dir.join(name));
+    }
+}
+
+/// A panic while logging must not take file logging down with it.
+fn lock() -> std::sync::MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl LogFile {
+    /// A new file named after the current time, with a counter on collision
+    /// (two rotations within one second).
+    fn create(dir: &Path) -> AppResult<Self> {
+        std::fs::create_dir_all(dir)?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let name = crate::commands_core::log_file_name(now);
+        let mut path = dir.join(&name);
+        let mut n = 1;
+        while path.exists() {
+            path = dir.join(name.replace(".log", &format!("_{}.log", n)));
+            n += 1;
+        }
+        let file = File::create(&path)?;
+        prune(dir);
+        Ok(Self {
+            path,
+            file,
+            written: 0,
+        })
+    }
+
+    fn write(&mut self, line: &str) {
+        if self.file.write_all(line.as_bytes()).is_ok() {
+            self.written += line.len() as u64;
+        }
+    }
+}
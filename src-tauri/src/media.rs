@@ -14,6 +14,29 @@ pub struct MediaInfo {
     /// "playing", "paused", "stopped", "unknown"
     pub playback_status: String,
     pub source_app: Option<String>,
+    /// Display name of `source_app` (e.g. "Spotify"), or the raw AUMID when it
+    /// can't be resolved
+    pub source_app_name: Option<String>,
+    /// Base64 PNG icon of `source_app`, when one could be resolved
+    pub source_app_icon: Option<String>,
+    /// `None` when the session doesn't report shuffle
+    pub shuffle: Option<bool>,
+    /// "none", "track" or "list"; `None` when the session doesn't report repeat
+    pub repeat: Option<String>,
+    /// 1.0 is normal speed; `None` when the session doesn't report a rate
+    pub playback_rate: Option<f64>,
+}
+
+/// An app that currently has a media session, as listed by `list_media_apps`.
+#[typeshare]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaApp {
+    pub aumid: String,
+    /// Resolved like `MediaInfo::source_app_name`
+    pub display_name: String,
+    /// "playing", "paused", "stopped", "unknown"
+    pub status: String,
 }
 
 /// Get current media playback info from the system.
@@ -36,22 +59,30 @@ pub fn get_media_info() -> AppResult<MediaInfo> {
         }
     };
 
-    let status = session
-        .GetPlaybackInfo()
-        .ok()
-        .and_then(|info| info.PlaybackStatus().ok())
-        .map(|s| {
-            use windows::Media::Control::GlobalSystemMediaTransportControlsSessionPlaybackStatus;
-            match s {
-                GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing => "playing",
-                GlobalSystemMediaTransportControlsSessionPlaybackStatus::Paused => "paused",
-                GlobalSystemMediaTransportControlsSessionPlaybackStatus::Stopped => "stopped",
-                GlobalSystemMediaTransportControlsSessionPlaybackStatus::Closed => "stopped",
-                _ => "unknown",
+    let playback = session.GetPlaybackInfo().ok();
+    let status = status_name(playback.as_ref()).to_string();
+
+    let shuffle = playback
+        .as_ref()
+        .and_then(|info| info.IsShuffleActive().ok())
+        .and_then(|r| r.Value().ok());
+    let repeat = playback
+        .as_ref()
+        .and_then(|info| info.AutoRepeatMode().ok())
+        .and_then(|r| r.Value().ok())
+        .map(|m| {
+            use windows::Media::MediaPlaybackAutoRepeatMode;
+            match m {
+                MediaPlaybackAutoRepeatMode::Track => "track",
+                MediaPlaybackAutoRepeatMode::List => "list",
+                _ => "none",
             }
-        })
-        .unwrap_or("unknown")
-        .to_string();
+            .to_string()
+        });
+    let playback_rate = playback
+        .as_ref()
+        .and_then(|info| info.PlaybackRate().ok())
+        .and_then(|r| r.Value().ok());
 
     let props = session
         .TryGetMediaPropertiesAsync()
@@ -82,15 +113,220 @@ pub fn get_media_info() -> AppResult<MediaInfo> {
         .map(|s| s.to_string())
         .filter(|s| !s.is_empty());
 
+    let (source_app_name, source_app_icon) = match source_app.as_deref() {
+        Some(aumid) => {
+            let (name, icon) = resolve_app(aumid);
+            (Some(name), icon)
+        }
+        None => (None, None),
+    };
+
     Ok(MediaInfo {
         title,
         artist,
         album,
         playback_status: status,
         source_app,
+        source_app_name,
+        source_app_icon,
+        shuffle,
+        repeat,
+        playback_rate,
     })
 }
 
+/// "playing", "paused", "stopped" or "unknown" for a session's playback info.
+#[cfg(target_os = "windows")]
+fn status_name(
+    playback: Option<
+        &windows::Media::Control::GlobalSystemMediaTransportControlsSessionPlaybackInfo,
+    >,
+) -> &'static str {
+    use windows::Media::Control::GlobalSystemMediaTransportControlsSessionPlaybackStatus;
+
+    match playback.and_then(|info| info.PlaybackStatus().ok()) {
+        Some(GlobalSystemMediaTransportControlsSessionPlaybackStatus::Playing) => "playing",
+        Some(GlobalSystemMediaTransportControlsSessionPlaybackStatus::Paused) => "paused",
+        Some(GlobalSystemMediaTransportControlsSessionPlaybackStatus::Stopped) => "stopped",
+        Some(GlobalSystemMediaTransportControlsSessionPlaybackStatus::Closed) => "stopped",
+        _ => "unknown",
+    }
+}
+
+/// Every app with a media session, paused ones included. Only the AUMID,
+/// name and status are read, so this is much cheaper than `get_media_info`.
+/// Sessions that close mid-enumeration are skipped.
+#[cfg(target_os = "windows")]
+pub fn list_media_apps() -> AppResult<Vec<MediaApp>> {
+    use windows::Media::Control::GlobalSystemMediaTransportControlsSessionManager;
+
+    let manager = GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
+        .map_err(|e| AppError::Media(format!("RequestAsync failed: {}", e)))?
+        .get()
+        .map_err(|e| AppError::Media(format!("Manager get failed: {}", e)))?;
+    let sessions = manager
+        .GetSessions()
+        .map_err(|e| AppError::Media(format!("GetSessions failed: {}", e)))?;
+    let count = sessions.Size().unwrap_or(0);
+
+    let mut apps = Vec::new();
+    for i in 0..count {
+        let Ok(session) = sessions.GetAt(i) else {
+            continue;
+        };
+        let Some(aumid) = session
+            .SourceAppUserModelId()
+            .ok()
+            .map(|s| s.to_string())
+            .filter(|s| !s.is_empty())
+        else {
+            continue;
+        };
+        let status = status_name(session.GetPlaybackInfo().ok().as_ref()).to_string();
+        let (display_name, _) = resolve_app(&aumid);
+        apps.push(MediaApp {
+            aumid,
+            display_name,
+            status,
+        });
+    }
+    Ok(apps)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn list_media_apps() -> AppResult<Vec<MediaApp>> {
+    Ok(Vec::new())
+}
+
+/// Display name and base64 PNG icon for an AUMID, looked up in the shell's
+/// AppsFolder, which lists packaged apps and Start menu shortcuts alike.
+/// Falls back to the AUMID itself. Cached, as both are stable per app.
+#[cfg(target_os = "windows")]
+fn resolve_app(aumid: &str) -> (String, Option<String>) {
+    use std::collections::HashMap;
+    use std::sync::{LazyLock, Mutex};
+
+    static RESOLVED: LazyLock<Mutex<HashMap<String, (String, Option<String>)>>> =
+        LazyLock::new(|| Mutex::new(HashMap::new()));
+
+    if let Some(hit) = RESOLVED.lock().unwrap().get(aumid) {
+        return hit.clone();
+    }
+    let resolved = unsafe { apps_folder_item(aumid) }
+        .map(|item| unsafe {
+            let name = shell_display_name(&item).unwrap_or_else(|| aumid.to_string());
+            (name, shell_icon_base64(&item))
+        })
+        .unwrap_or_else(|| (aumid.to_string(), None));
+    RESOLVED
+        .lock()
+        .unwrap()
+        .insert(aumid.to_string(), resolved.clone());
+    resolved
+}
+
+/// Join COM on the calling thread before shell or Core Audio calls.
+#[cfg(target_os = "windows")]
+fn ensure_com() {
+    use windows::Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED};
+
+    // Already initialized (in either apartment) is fine for these calls
+    unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+    }
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn apps_folder_item(aumid: &str) -> Option<windows::Win32::UI::Shell::IShellItem> {
+    use windows::Win32::System::Com::IBindCtx;
+    use windows::Win32::UI::Shell::SHCreateItemFromParsingName;
+
+    ensure_com();
+    let path = windows::core::HSTRING::from(format!("shell:AppsFolder\\{}", aumid));
+    SHCreateItemFromParsingName(&path, None::<&IBindCtx>).ok()
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn shell_display_name(item: &windows::Win32::UI::Shell::IShellItem) -> Option<String> {
+    use windows::Win32::System::Com::CoTaskMemFree;
+    use windows::Win32::UI::Shell::SIGDN_NORMALDISPLAY;
+
+    let raw = item.GetDisplayName(SIGDN_NORMALDISPLAY).ok()?;
+    let name = raw.to_string().ok();
+    CoTaskMemFree(Some(raw.0 as *const _));
+    name.filter(|n| !n.is_empty())
+}
+
+/// Edge length of the app icon in pixels.
+#[cfg(target_os = "windows")]
+const APP_ICON_SIZE: i32 = 32;
+
+#[cfg(target_os = "windows")]
+unsafe fn shell_icon_base64(item: &windows::Win32::UI::Shell::IShellItem) -> Option<String> {
+    use base64::Engine;
+    use windows::core::Interface;
+    use windows::Win32::Foundation::{HWND, SIZE};
+    use windows::Win32::Graphics::Gdi::*;
+    use windows::Win32::UI::Shell::{IShellItemImageFactory, SIIGBF_ICONONLY};
+
+    let factory = item.cast::<IShellItemImageFactory>().ok()?;
+    let bitmap = factory
+        .GetImage(
+            SIZE {
+                cx: APP_ICON_SIZE,
+                cy: APP_ICON_SIZE,
+            },
+            SIIGBF_ICONONLY,
+        )
+        .ok()?;
+
+    // The shell may hand back another size than requested
+    let mut info = BITMAP::default();
+    let got = GetObjectW(
+        bitmap,
+        std::mem::size_of::<BITMAP>() as i32,
+        Some(&mut info as *mut _ as *mut _),
+    );
+    let (w, h) = (info.bmWidth, info.bmHeight);
+    let mut bgra = vec![0u8; w.max(0) as usize * h.max(0) as usize * 4];
+    let mut bmi = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: w,
+            biHeight: -h, // top-down
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let rows = if got == 0 || bgra.is_empty() {
+        0
+    } else {
+        let dc = GetDC(HWND::default());
+        let rows = GetDIBits(
+            dc,
+            bitmap,
+            0,
+            h as u32,
+            Some(bgra.as_mut_ptr() as *mut _),
+            &mut bmi,
+            DIB_RGB_COLORS,
+        );
+        ReleaseDC(HWND::default(), dc);
+        rows
+    };
+    let _ = DeleteObject(bitmap);
+    if rows != h {
+        return None;
+    }
+
+    let rgba = crate::commands_core::premultiplied_bgra_to_rgba(&bgra);
+    let png = crate::commands_core::encode_png(&rgba, w as u32, h as u32).ok()?;
+    Some(base64::engine::general_purpose::STANDARD.encode(png))
+}
+
 #[cfg(not(target_os = "windows"))]
 pub fn get_media_info() -> AppResult<MediaInfo> {
     Ok(MediaInfo {
@@ -182,3 +418,228 @@ pub fn media_prev() -> AppResult<()> {
 pub fn media_prev() -> AppResult<()> {
     Err(AppError::Media("Not supported on this platform".into()))
 }
+
+/// Flip shuffle on the current media session.
+#[cfg(target_os = "windows")]
+pub fn media_toggle_shuffle() -> AppResult<()> {
+    use windows::Media::Control::GlobalSystemMediaTransportControlsSessionManager;
+
+    let manager = GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
+        .map_err(|e| AppError::Media(format!("RequestAsync failed: {}", e)))?
+        .get()
+        .map_err(|e| AppError::Media(format!("Manager get failed: {}", e)))?;
+
+    let session = manager
+        .GetCurrentSession()
+        .map_err(|e| AppError::Media(format!("No active session: {}", e)))?;
+
+    let info = session
+        .GetPlaybackInfo()
+        .map_err(|e| AppError::Media(format!("GetPlaybackInfo failed: {}", e)))?;
+    let active = info
+        .IsShuffleActive()
+        .and_then(|r| r.Value())
+        .map_err(|_| AppError::Media("Shuffle not supported by this session".into()))?;
+
+    let changed = session
+        .TryChangeShuffleActiveAsync(!active)
+        .map_err(|e| AppError::Media(format!("ChangeShuffle failed: {}", e)))?
+        .get()
+        .map_err(|e| AppError::Media(format!("ChangeShuffle get failed: {}", e)))?;
+    if !changed {
+        return Err(AppError::Media(
+            "Shuffle not supported by this session".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn media_toggle_shuffle() -> AppResult<()> {
+    Err(AppError::Media("Not supported on this platform".into()))
+}
+
+/// Set repeat on the current media session: "none", "track" or "list".
+#[cfg(target_os = "windows")]
+pub fn media_set_repeat(mode: &str) -> AppResult<()> {
+    use windows::Media::Control::GlobalSystemMediaTransportControlsSessionManager;
+    use windows::Media::MediaPlaybackAutoRepeatMode;
+
+    let mode = match mode {
+        "none" => MediaPlaybackAutoRepeatMode::None,
+        "track" => MediaPlaybackAutoRepeatMode::Track,
+        "list" => MediaPlaybackAutoRepeatMode::List,
+        other => {
+            return Err(AppError::Validation(format!(
+                "Unknown repeat mode: {} (expected one of: none, track, list)",
+                other
+            )))
+        }
+    };
+
+    let manager = GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
+        .map_err(|e| AppError::Media(format!("RequestAsync failed: {}", e)))?
+        .get()
+        .map_err(|e| AppError::Media(format!("Manager get failed: {}", e)))?;
+
+    let session = manager
+        .GetCurrentSession()
+        .map_err(|e| AppError::Media(format!("No active session: {}", e)))?;
+
+    let changed = session
+        .TryChangeAutoRepeatModeAsync(mode)
+        .map_err(|e| AppError::Media(format!("ChangeAutoRepeatMode failed: {}", e)))?
+        .get()
+        .map_err(|e| AppError::Media(format!("ChangeAutoRepeatMode get failed: {}", e)))?;
+    if !changed {
+        return Err(AppError::Media(
+            "Repeat not supported by this session".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn media_set_repeat(_mode: &str) -> AppResult<()> {
+    Err(AppError::Media("Not supported on this platform".into()))
+}
+
+/// Volume (0.0–1.0) of the audio session belonging to the current media app.
+#[cfg(target_os = "windows")]
+pub fn media_get_volume() -> AppResult<f32> {
+    let volumes = session_volumes()?;
+    unsafe { volumes[0].GetMasterVolume() }
+        .map_err(|e| AppError::Media(format!("GetMasterVolume failed: {}", e)))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn media_get_volume() -> AppResult<f32> {
+    Err(AppError::Media("Not supported on this platform".into()))
+}
+
+/// Set the volume of every audio session belonging to the current media app.
+/// `level` is clamped to 0.0–1.0.
+#[cfg(target_os = "windows")]
+pub fn media_set_volume(level: f32) -> AppResult<()> {
+    let level = if level.is_nan() {
+        0.0
+    } else {
+        level.clamp(0.0, 1.0)
+    };
+    for volume in session_volumes()? {
+        unsafe { volume.SetMasterVolume(level, std::ptr::null()) }
+            .map_err(|e| AppError::Media(format!("SetMasterVolume failed: {}", e)))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn media_set_volume(_level: f32) -> AppResult<()> {
+    Err(AppError::Media("Not supported on this platform".into()))
+}
+
+/// Volume controls of the Core Audio sessions on the default render device
+/// whose process matches the current media session's app. Never empty.
+#[cfg(target_os = "windows")]
+fn session_volumes() -> AppResult<Vec<windows::Win32::Media::Audio::ISimpleAudioVolume>> {
+    use crate::commands_core::aumid_matches_exe;
+    use windows::core::Interface;
+    use windows::Media::Control::GlobalSystemMediaTransportControlsSessionManager;
+    use windows::Win32::Media::Audio::{
+        eMultimedia, eRender, IAudioSessionControl2, IAudioSessionManager2, IMMDeviceEnumerator,
+        ISimpleAudioVolume, MMDeviceEnumerator,
+    };
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_ALL};
+
+    let manager = GlobalSystemMediaTransportControlsSessionManager::RequestAsync()
+        .map_err(|e| AppError::Media(format!("RequestAsync failed: {}", e)))?
+        .get()
+        .map_err(|e| AppError::Media(format!("Manager get failed: {}", e)))?;
+
+    let session = manager
+        .GetCurrentSession()
+        .map_err(|e| AppError::Media(format!("No active session: {}", e)))?;
+    let aumid = session
+        .SourceAppUserModelId()
+        .map_err(|e| AppError::Media(format!("SourceAppUserModelId failed: {}", e)))?
+        .to_string();
+
+    ensure_com();
+    unsafe {
+        let enumerator: IMMDeviceEnumerator =
+            CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)
+                .map_err(|e| AppError::Media(format!("MMDeviceEnumerator failed: {}", e)))?;
+        let device = enumerator
+            .GetDefaultAudioEndpoint(eRender, eMultimedia)
+            .map_err(|e| AppError::Media(format!("No audio output device: {}", e)))?;
+        let sessions = device
+            .Activate::<IAudioSessionManager2>(CLSCTX_ALL, None)
+            .and_then(|m| m.GetSessionEnumerator())
+            .map_err(|e| AppError::Media(format!("Audio session enumeration failed: {}", e)))?;
+        let count = sessions
+            .GetCount()
+            .map_err(|e| AppError::Media(format!("Audio session count failed: {}", e)))?;
+
+        let mut volumes = Vec::new();
+        for i in 0..count {
+            let Ok(control) = sessions
+                .GetSession(i)
+                .and_then(|c| c.cast::<IAudioSessionControl2>())
+            else {
+                continue;
+            };
+            let Ok(pid) = control.GetProcessId() else {
+                continue;
+            };
+            let Some(exe) = process_exe_name(pid) else {
+                continue;
+            };
+            if !aumid_matches_exe(&aumid, &exe) {
+                continue;
+            }
+            if let Ok(volume) = control.cast::<ISimpleAudioVolume>() {
+                volumes.push(volume);
+            }
+        }
+
+        if volumes.is_empty() {
+            return Err(AppError::Media(format!(
+                "No audio session found for {}",
+                aumid
+            )));
+        }
+        Ok(volumes)
+    }
+}
+
+/// Image file name (e.g. "Spotify.exe") of process `pid`.
+#[cfg(target_os = "windows")]
+unsafe fn process_exe_name(pid: u32) -> Option<String> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    if pid == 0 {
+        return None;
+    }
+    let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+    let mut buf = [0u16; 1024];
+    let mut len = buf.len() as u32;
+    let ok = QueryFullProcessImageNameW(
+        process,
+        PROCESS_NAME_WIN32,
+        windows::core::PWSTR(buf.as_mut_ptr()),
+        &mut len,
+    )
+    .is_ok();
+    let _ = CloseHandle(process);
+    if !ok {
+        return None;
+    }
+    let path = String::from_utf16_lossy(&buf[..len as usize]);
+    path.rsplit('\\').next().map(str::to_string)
+}
@@ -0,0 +1,241 @@
+//! OAuth flow state — PKCE verifiers and `state` values for in-flight logins.
+//!
+//! Verifiers never leave the backend until the matching callback arrives.
+//! Each `state` is single-use and expires after `FLOW_TTL`; callback deep links
+//! whose state doesn't match an in-flight flow are dropped (CSRF protection).
+//!
+//! Where the `mywallpaper://` scheme is blocked by policy, a one-shot loopback
+//! HTTP server can receive the redirect instead; it feeds the same deep-link path.
+
+use crate::commands_core;
+use crate::error::{AppError, AppResult};
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use typeshare::typeshare;
+
+const FLOW_TTL: Duration = Duration::from_secs(10 * 60);
+/// How long the loopback server waits for the browser redirect.
+const LOOPBACK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+struct PendingFlow {
+    /// Set for PKCE flows started with `begin`; `None` for flows only seen
+    /// through `open_oauth_in_browser`.
+    pkce: Option<(String, String)>,
+    created: Instant,
+    /// A callback for this state was already delivered to the frontend.
+    callback_seen: bool,
+}
+
+static PENDING: LazyLock<Mutex<HashMap<String, PendingFlow>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Returned by `begin_oauth`: what the frontend puts in the authorize URL.
+#[typeshare]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthBegin {
+    pub state: String,
+    pub code_challenge: String,
+    pub code_challenge_method: String,
+}
+
+/// Returned by `complete_oauth`: everything needed for the token exchange.
+#[typeshare]
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthComplete {
+    pub provider: String,
+    pub code: String,
+    pub code_verifier: String,
+}
+
+/// Random URL-safe token (base64url of `bytes` random bytes).
+fn random_token(bytes: usize) -> AppResult<String> {
+    use base64::Engine;
+    let mut buf = vec![0u8; bytes];
+    getrandom::getrandom(&mut buf)
+        .map_err(|e| AppError::OAuth(format!("RNG unavailable: {}", e)))?;
+    Ok(base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(buf))
+}
+
+fn prune_expired(pending: &mut HashMap<String, PendingFlow>) {
+    pending.retain(|_, f| f.created.elapsed() < FLOW_TTL);
+}
+
+/// Start a PKCE flow: generate verifier + S256 challenge and a random state.
+pub fn begin(provider: String) -> AppResult<OAuthBegin> {
+    commands_core::validate_oauth_provider(&provider)?;
+    // 32 bytes → 43 chars, the minimum verifier length allowed by RFC 7636
+    let verifier = random_token(32)?;
+    let state = random_token(16)?;
+    let challenge = commands_core::pkce_challenge(&verifier);
+
+    let mut pending = PENDING.lock().unwrap();
+    prune_expired(&mut pending);
+    pending.insert(
+        state.clone(),
+        PendingFlow {
+            pkce: Some((provider.clone(), verifier)),
+            created: Instant::now(),
+            callback_seen: false,
+        },
+    );
+    info!("[oauth] Flow started for {}", provider);
+
+    Ok(OAuthBegin {
+        state,
+        code_challenge: challenge,
+        code_challenge_method: "S256".into(),
+    })
+}
+
+/// Finish a PKCE flow from the callback deep link. The state is consumed
+/// whether or not the flow is still valid, so a callback can't be replayed.
+pub fn complete(url: &str) -> AppResult<OAuthComplete> {
+    let callback = commands_core::parse_oauth_callback(url)?;
+
+    let mut pending = PENDING.lock().unwrap();
+    prune_expired(&mut pending);
+    let Some((provider, verifier)) = pending.remove(&callback.state).and_then(|f| f.pkce) else {
+        warn!("[oauth] Rejected callback with unknown or expired state");
+        return Err(AppError::OAuth("State mismatch".into()));
+    };
+
+    if let Some(error) = callback.error {
+        info!("[oauth] {} returned error: {}", provider, error);
+        return Err(AppError::OAuth(format!("Authorization failed: {}", error)));
+    }
+    Ok(OAuthComplete {
+        provider,
+        code: callback.code.unwrap_or_default(),
+        code_verifier: verifier,
+    })
+}
+
+/// Remember the `state` of an authorize URL opened in the browser so its
+/// callback can be matched. URLs without a state are opened untracked.
+pub fn track_outgoing_url(url: &str) {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return;
+    };
+    let Some(state) = parsed
+        .query_pairs()
+        .find(|(k, _)| k == "state")
+        .map(|(_, v)| v.into_owned())
+        .filter(|v| !v.is_empty())
+    else {
+        warn!("[oauth] Authorize URL has no state parameter; its callback will be rejected");
+        return;
+    };
+
+    let mut pending = PENDING.lock().unwrap();
+    prune_expired(&mut pending);
+    // A PKCE flow from `begin` already owns this state — keep its verifier
+    pending.entry(state).or_insert_with(|| PendingFlow {
+        pkce: None,
+        created: Instant::now(),
+        callback_seen: false,
+    });
+}
+
+/// Gate for callback deep links before they reach the frontend.
+/// Returns false when no flow with this state is in progress, it expired, or
+/// its callback was already delivered (replay).
+pub fn accept_callback(url: &str) -> bool {
+    let Ok(callback) = commands_core::parse_oauth_callback(url) else {
+        return false;
+    };
+
+    let mut pending = PENDING.lock().unwrap();
+    prune_expired(&mut pending);
+    match pending.get_mut(&callback.state) {
+        Some(flow) if !flow.callback_seen => {
+            if flow.pkce.is_some() {
+                // Kept until `complete` hands out the verifier
+                flow.callback_seen = true;
+            } else {
+                pending.remove(&callback.state);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Start a one-shot callback server on an ephemeral loopback port and return
+/// its `redirect_uri`. `/callback` requests are delivered like a callback deep
+/// link (state check included); the server shuts down once one is accepted.
+pub fn start_loopback(app: tauri::AppHandle) -> AppResult<String> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    // Non-blocking accept so the thread can give up at the deadline
+    listener.set_nonblocking(true)?;
+    let port = listener.local_addr()?.port();
+    std::thread::spawn(move || serve_loopback(&app, listener));
+    info!("[oauth] Loopback server listening on port {}", port);
+    Ok(format!("http://127.0.0.1:{}/callback", port))
+}
+
+fn serve_loopback(app: &tauri::AppHandle, listener: TcpListener) {
+    let deadline = Instant::now() + LOOPBACK_TIMEOUT;
+    while Instant::now() < deadline {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if handle_loopback_request(app, stream) {
+                    info!("[oauth] Loopback callback received, server closed");
+                    return;
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            Err(e) => {
+                warn!("[oauth] Loopback server failed: {}", e);
+                return;
+            }
+        }
+    }
+    warn!("[oauth] Loopback server timed out without a callback");
+}
+
+/// Answer one request; true once the callback was accepted and handed off.
+/// A callback with a stale or unknown state gets an error page and leaves the
+/// server listening for the real one.
+fn handle_loopback_request(app: &tauri::AppHandle, mut stream: TcpStream) -> bool {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let mut buf = [0u8; 8192];
+    let n = stream.read(&mut buf).unwrap_or(0);
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let link = request
+        .lines()
+        .next()
+        .and_then(commands_core::loopback_request_to_deep_link);
+    let delivered = link
+        .as_deref()
+        .is_some_and(|link| crate::deliver_deep_link(app, link));
+
+    let (status, body) = match (&link, delivered) {
+        (Some(_), true) => (
+            "200 OK",
+            "Login received. You can close this tab and return to MyWallpaper.",
+        ),
+        (Some(_), false) => (
+            "400 Bad Request",
+            "This login link is invalid or has expired. Please start the login again from MyWallpaper.",
+        ),
+        (None, _) => ("404 Not Found", "Not found"),
+    };
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    delivered
+}
@@ -0,0 +1,93 @@
+//! Performance hints — tells the frontend when to lower animation cost.
+//!
+//! Tracks the Windows battery saver flag and the "Show animations in Windows"
+//! accessibility setting, emitting `performance-hint` whenever either changes.
+
+use crate::events::{AppEvent, EmitAppEvent};
+use log::info;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use typeshare::typeshare;
+
+static WATCHER_RUNNING: AtomicBool = AtomicBool::new(false);
+static CURRENT: Mutex<PerformanceHint> = Mutex::new(PerformanceHint {
+    reduce_motion: false,
+    low_power: false,
+});
+
+/// Both settings change rarely and by hand; a slow poll is plenty.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[typeshare]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceHint {
+    /// The user turned off animations in Windows accessibility settings.
+    pub reduce_motion: bool,
+    /// Battery saver is on.
+    pub low_power: bool,
+}
+
+/// Current hint, as of the last poll.
+pub fn get() -> PerformanceHint {
+    *CURRENT.lock().unwrap()
+}
+
+/// Start the watcher. The first poll emits the initial state if it differs
+/// from the all-clear default.
+pub fn init(app: &tauri::AppHandle) {
+    if WATCHER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        let hint = read_hint();
+        let changed = {
+            let mut current = CURRENT.lock().unwrap();
+            std::mem::replace(&mut *current, hint) != hint
+        };
+        if changed {
+            info!(
+                "[performance] Reduce motion: {}, low power: {}",
+                hint.reduce_motion, hint.low_power
+            );
+            let _ = app.emit_app_event(&AppEvent::PerformanceHint(hint));
+            crate::frame_rate::refresh(&app);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    });
+}
+
+#[cfg(target_os = "windows")]
+fn read_hint() -> PerformanceHint {
+    use windows::Win32::Foundation::BOOL;
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+    };
+
+    let mut animations = BOOL(1);
+    let mut power = SYSTEM_POWER_STATUS::default();
+    unsafe {
+        let reduce_motion = SystemParametersInfoW(
+            SPI_GETCLIENTAREAANIMATION,
+            0,
+            Some(&mut animations as *mut BOOL as *mut _),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+        .is_ok()
+            && !animations.as_bool();
+        // SystemStatusFlag is 1 while battery saver is on
+        let low_power = GetSystemPowerStatus(&mut power).is_ok() && power.SystemStatusFlag == 1;
+        PerformanceHint {
+            reduce_motion,
+            low_power,
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_hint() -> PerformanceHint {
+    get()
+}
@@ -0,0 +1,139 @@
+//! Wallpaper playlist — ordered wallpaper ids rotated on a timer.
+//!
+//! The playlist (including the current index and when it last changed) is
+//! persisted in settings, so a restart resumes where it left off instead of
+//! starting the interval over from the first wallpaper.
+
+use crate::error::AppResult;
+use crate::events::{AppEvent, EmitAppEvent};
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use typeshare::typeshare;
+
+static PLAYLIST: LazyLock<Mutex<Playlist>> = LazyLock::new(|| Mutex::new(Playlist::default()));
+static TIMER_RUNNING: AtomicBool = AtomicBool::new(false);
+
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[typeshare]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Playlist {
+    pub ids: Vec<String>,
+    /// Rotation interval; 0 disables automatic rotation.
+    pub interval_secs: u64,
+    pub index: u32,
+    /// Unix time of the last change, used to resume the interval after a restart.
+    pub changed_at: u64,
+}
+
+impl Playlist {
+    fn current(&self) -> Option<&String> {
+        self.ids.get(self.index as usize)
+    }
+}
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Restore the persisted playlist and start the rotation timer.
+pub fn init(app: &tauri::AppHandle) {
+    let mut saved = crate::settings::get().playlist;
+    if saved.index as usize >= saved.ids.len() {
+        saved.index = 0;
+    }
+    if !saved.ids.is_empty() {
+        info!(
+            "[playlist] Restored {} wallpapers at index {}",
+            saved.ids.len(),
+            saved.index
+        );
+    }
+    *PLAYLIST.lock().unwrap() = saved;
+    start_timer(app.clone());
+}
+
+/// Snapshot of the current playlist.
+pub fn get() -> Playlist {
+    PLAYLIST.lock().unwrap().clone()
+}
+
+/// Replace the playlist. Keeps the current wallpaper if it is still in the list.
+pub fn set(app: &tauri::AppHandle, ids: Vec<String>, interval_secs: u64) -> AppResult<()> {
+    crate::commands_core::validate_playlist(&ids, interval_secs)?;
+    let changed = {
+        let mut pl = PLAYLIST.lock().unwrap();
+        let current = pl.current().cloned();
+        let index = current
+            .as_ref()
+            .and_then(|c| ids.iter().position(|id| id == c))
+            .unwrap_or(0);
+        let changed = current.as_ref() != ids.get(index);
+        *pl = Playlist {
+            ids,
+            interval_secs,
+            index: index as u32,
+            changed_at: now_secs(),
+        };
+        persist(&pl);
+        changed.then(|| pl.ids[index].clone())
+    };
+    info!("[playlist] Set, interval {}s", interval_secs);
+    if let Some(id) = changed {
+        emit_changed(app, id);
+    }
+    Ok(())
+}
+
+/// Step forward (`1`) or back (`-1`) and announce the new wallpaper.
+pub fn advance(app: &tauri::AppHandle, step: isize) -> Option<String> {
+    let id = {
+        let mut pl = PLAYLIST.lock().unwrap();
+        let len = pl.ids.len();
+        if len == 0 {
+            return None;
+        }
+        let index = (pl.index as isize + step).rem_euclid(len as isize) as usize;
+        pl.index = index as u32;
+        pl.changed_at = now_secs();
+        persist(&pl);
+        pl.ids[index].clone()
+    };
+    emit_changed(app, id.clone());
+    Some(id)
+}
+
+fn emit_changed(app: &tauri::AppHandle, id: String) {
+    info!("[playlist] Wallpaper changed: {}", id);
+    let _ = app.emit_app_event(&AppEvent::WallpaperChanged { id });
+}
+
+fn persist(pl: &Playlist) {
+    let snapshot = pl.clone();
+    crate::settings::update(|s| s.playlist = snapshot);
+}
+
+fn start_timer(app: tauri::AppHandle) {
+    if TIMER_RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(move || loop {
+        std::thread::sleep(TICK_INTERVAL);
+        let due = {
+            let pl = PLAYLIST.lock().unwrap();
+            pl.interval_secs > 0
+                && pl.ids.len() > 1
+                && now_secs().saturating_sub(pl.changed_at) >= pl.interval_secs
+        };
+        if due {
+            advance(&app, 1);
+        }
+    });
+}
@@ -0,0 +1,240 @@
+//! Persisted user preferences.
+//!
+//! Stored as JSON in the Tauri app config dir. Missing or unreadable files
+//! fall back to defaults so a bad config never blocks startup. Writes go
+//! through a temp file + rename, and every change emits `settings-changed`.
+
+use crate::error::{AppError, AppResult};
+use crate::events::{AppEvent, EmitAppEvent};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex, OnceLock};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub show_desktop_icons: bool,
+    pub tray_stats: bool,
+    pub auto_update_check: bool,
+    pub skipped_version: Option<String>,
+    /// `UpdateChannel` name; `None` is stable.
+    pub update_channel: Option<String>,
+    pub discord_enabled: bool,
+    pub idle_timeout_secs: Option<u64>,
+    pub playlist: crate::playlist::Playlist,
+    pub watchdog_interval_secs: Option<u64>,
+    pub pause_apps: Vec<String>,
+    /// `LayerMode` name; parsed on load so a bad value can't break the whole file
+    pub layer_mode: String,
+    /// RGB shown behind the WebView before the wallpaper paints.
+    pub background_color: [u8; 3],
+    /// Mouse-move forwarding cap in Hz; 0 forwards every move.
+    pub mouse_forward_hz: u32,
+    /// Monitor index the wallpaper is bound to; `None` spans all monitors.
+    pub wallpaper_monitor: Option<usize>,
+    /// Wallpaper id per stable monitor id (`MonitorInfo::id`); kept while a
+    /// monitor is unplugged.
+    pub monitor_wallpapers: std::collections::BTreeMap<String, String>,
+    /// Size the wallpaper to the monitor work areas instead of under the taskbar.
+    pub respect_taskbar: bool,
+    /// Base animation frame rate, before battery and visibility caps.
+    pub target_fps: u32,
+    /// Let the hook DLL swallow spurious WM_MOUSELEAVE on the WebView.
+    pub mouseleave_suppression: bool,
+    /// Forward clicks to the desktop icons as well as the wallpaper.
+    pub icon_interaction: bool,
+    /// Accelerator bound to the layer toggle, e.g. "Ctrl+Alt+W".
+    pub layer_shortcut: Option<String>,
+    /// Runtime log level name; `None` keeps the build-time default.
+    pub log_level: Option<String>,
+    /// `NetworkFilter` mode: "physical", "all" or comma-separated interface names.
+    pub network_filter: String,
+    /// How long startup waits for the WebView2 controller before reporting
+    /// "controller-timeout" (it keeps retrying in the background).
+    pub controller_timeout_ms: u64,
+    /// Samples kept per category for `get_metric_history`.
+    pub metric_history_length: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            show_desktop_icons: true,
+            tray_stats: false,
+            auto_update_check: true,
+            skipped_version: None,
+            update_channel: None,
+            discord_enabled: true,
+            idle_timeout_secs: None,
+            playlist: Default::default(),
+            watchdog_interval_secs: Some(crate::window_layer::DEFAULT_WATCHDOG_INTERVAL_SECS),
+            pause_apps: Vec::new(),
+            layer_mode: crate::commands_core::LayerMode::default().name().into(),
+            background_color: [0, 0, 0],
+            mouse_forward_hz: 0,
+            wallpaper_monitor: None,
+            monitor_wallpapers: Default::default(),
+            respect_taskbar: false,
+            target_fps: crate::commands_core::DEFAULT_TARGET_FPS,
+            mouseleave_suppression: true,
+            icon_interaction: true,
+            layer_shortcut: None,
+            log_level: None,
+            network_filter: "physical".into(),
+            controller_timeout_ms: 1000,
+            metric_history_length: crate::commands_core::DEFAULT_HISTORY_LENGTH,
+        }
+    }
+}
+
+static SETTINGS: LazyLock<Mutex<Settings>> = LazyLock::new(|| Mutex::new(Settings::default()));
+static SETTINGS_PATH: OnceLock<PathBuf> = OnceLock::new();
+static APP_HANDLE: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+/// Resolve the settings path and load it. Call once during setup, before
+/// anything reads preferences.
+pub fn init(app: &tauri::AppHandle) {
+    use tauri::Manager;
+
+    let dir = match app.path().app_config_dir() {
+        Ok(d) => d,
+        Err(e) => {
+            error!("[settings] Could not resolve config dir: {}", e);
+            return;
+        }
+    };
+    let path = dir.join(SETTINGS_FILE);
+
+    let loaded = match std::fs::read_to_string(&path) {
+        Ok(raw) => serde_json::from_str::<Settings>(&raw).unwrap_or_else(|e| {
+            warn!("[settings] Corrupt settings file, using defaults: {}", e);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    };
+    info!("[settings] Loaded from {}", path.display());
+
+    *SETTINGS.lock().unwrap() = loaded;
+    let _ = SETTINGS_PATH.set(path);
+    let _ = APP_HANDLE.set(app.clone());
+}
+
+/// Snapshot of the current settings.
+pub fn get() -> Settings {
+    SETTINGS.lock().unwrap().clone()
+}
+
+/// Mutate the settings and write them back to disk.
+pub fn update(f: impl FnOnce(&mut Settings)) {
+    let _ = try_update(|s| {
+        f(s);
+        Ok(())
+    });
+}
+
+/// Like `update`, but `f` may reject the change; nothing is written then.
+fn try_update(f: impl FnOnce(&mut Settings) -> AppResult<()>) -> AppResult<()> {
+    let (before, after) = {
+        let mut guard = SETTINGS.lock().unwrap();
+        let before = to_map(&guard);
+        f(&mut guard)?;
+        // Saved under the lock: concurrent updates share the temp file, and an
+        // older snapshot must not be renamed over a newer one
+        if let Err(e) = save(&guard) {
+            error!("[settings] Failed to save: {}", e);
+        }
+        (before, to_map(&guard))
+    };
+    let keys: Vec<String> = after
+        .iter()
+        .filter(|(k, v)| before.get(*k) != Some(*v))
+        .map(|(k, _)| k.clone())
+        .collect();
+    if !keys.is_empty() {
+        if let Some(app) = APP_HANDLE.get() {
+            let _ = app.emit_app_event(&AppEvent::SettingsChanged { keys });
+        }
+    }
+    Ok(())
+}
+
+/// Read one setting by its camelCase key.
+pub fn get_value(key: &str) -> AppResult<serde_json::Value> {
+    to_map(&get())
+        .remove(key)
+        .ok_or_else(|| AppError::Validation(format!("Unknown setting: {}", key)))
+}
+
+/// Write one setting by its camelCase key. The value must have the field's
+/// type. This only persists it: settings applied at runtime (layer mode, mouse
+/// rate, ...) must go through their own setters instead.
+pub fn set_value(key: &str, value: serde_json::Value) -> AppResult<()> {
+    try_update(|s| {
+        let mut map = to_map(s);
+        match map.get_mut(key) {
+            Some(slot) => *slot = value,
+            None => return Err(AppError::Validation(format!("Unknown setting: {}", key))),
+        }
+        *s = serde_json::from_value(serde_json::Value::Object(map))
+            .map_err(|e| AppError::Validation(format!("Invalid value for {}: {}", key, e)))?;
+        Ok(())
+    })
+}
+
+fn to_map(settings: &Settings) -> serde_json::Map<String, serde_json::Value> {
+    match serde_json::to_value(settings) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    }
+}
+
+fn save(settings: &Settings) -> std::io::Result<()> {
+    let Some(path) = SETTINGS_PATH.get() else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(settings).map_err(std::io::Error::other)?;
+    // Rename over the old file so a crash mid-write never leaves it truncated
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, json)?;
+    std::fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrent_updates_both_persist() {
+        let dir = std::env::temp_dir().join(format!("mwp-settings-{}", std::process::id()));
+        let path = dir.join(SETTINGS_FILE);
+        SETTINGS_PATH.set(path.clone()).unwrap();
+
+        let threads = [
+            std::thread::spawn(|| {
+                for _ in 0..50 {
+                    update(|s| s.tray_stats = true);
+                }
+            }),
+            std::thread::spawn(|| {
+                for _ in 0..50 {
+                    update(|s| s.show_desktop_icons = false);
+                }
+            }),
+        ];
+        for t in threads {
+            t.join().unwrap();
+        }
+
+        let saved: Settings =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(saved.tray_stats);
+        assert!(!saved.show_desktop_icons);
+    }
+}
@@ -0,0 +1,120 @@
+//! Global shortcuts — currently a single user-chosen key that toggles the
+//! window layer between the desktop and a normal window.
+//!
+//! Accelerators are parsed with the global-shortcut plugin before registering,
+//! so an invalid string or a combo another app already owns is reported as an
+//! error instead of silently doing nothing.
+
+use crate::error::{AppError, AppResult};
+use log::{error, info, warn};
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+use typeshare::typeshare;
+
+const ACTION_TOGGLE_LAYER: &str = "toggle-layer";
+
+static LAYER_SHORTCUT: Mutex<Option<Shortcut>> = Mutex::new(None);
+
+/// A registered accelerator and what it does.
+#[typeshare]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisteredShortcut {
+    pub accelerator: String,
+    pub action: String,
+}
+
+/// The plugin, with the handler that dispatches our shortcuts.
+pub fn plugin() -> tauri::plugin::TauriPlugin<tauri::Wry> {
+    tauri_plugin_global_shortcut::Builder::new()
+        .with_handler(|_app, shortcut, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            if LAYER_SHORTCUT.lock().unwrap().as_ref() == Some(shortcut) {
+                match crate::window_layer::toggle_window_layer() {
+                    Ok(mode) => info!("[shortcuts] Layer toggled to {}", mode),
+                    Err(e) => error!("[shortcuts] Layer toggle failed: {}", e),
+                }
+            }
+        })
+        .build()
+}
+
+/// Re-register the persisted layer shortcut. A shortcut that can no longer be
+/// registered (another app took it) is logged and kept in settings.
+pub fn init(app: &tauri::AppHandle) {
+    let Some(accelerator) = crate::settings::get().layer_shortcut else {
+        return;
+    };
+    if let Err(e) = register(app, &accelerator) {
+        warn!("[shortcuts] Could not restore layer shortcut: {}", e);
+    }
+}
+
+/// Parse an accelerator such as "Ctrl+Alt+W".
+fn parse(accelerator: &str) -> AppResult<Shortcut> {
+    accelerator
+        .parse::<Shortcut>()
+        .map_err(|e| AppError::Validation(format!("Invalid shortcut {}: {}", accelerator, e)))
+}
+
+fn register(app: &tauri::AppHandle, accelerator: &str) -> AppResult<()> {
+    let shortcut = parse(accelerator)?;
+    let mut current = LAYER_SHORTCUT.lock().unwrap();
+    if current.as_ref() == Some(&shortcut) {
+        return Ok(());
+    }
+    let manager = app.global_shortcut();
+    if manager.is_registered(shortcut) {
+        return Err(AppError::Validation(format!(
+            "Shortcut {} is already registered",
+            accelerator
+        )));
+    }
+    // Claim the new combo first so a failure leaves the old one working
+    manager.register(shortcut).map_err(|e| {
+        AppError::Validation(format!("Shortcut {} is unavailable: {}", accelerator, e))
+    })?;
+    if let Some(old) = current.replace(shortcut) {
+        let _ = manager.unregister(old);
+    }
+    info!("[shortcuts] Layer toggle bound to {}", accelerator);
+    Ok(())
+}
+
+/// Bind the layer toggle to `accelerator`, replacing any previous binding. Persisted.
+#[tauri::command]
+pub fn register_layer_shortcut(app: tauri::AppHandle, accelerator: String) -> AppResult<()> {
+    register(&app, &accelerator)?;
+    crate::settings::update(|s| s.layer_shortcut = Some(accelerator));
+    Ok(())
+}
+
+/// Remove the layer toggle binding. Persisted.
+#[tauri::command]
+pub fn unregister_layer_shortcut(app: tauri::AppHandle) -> AppResult<()> {
+    if let Some(old) = LAYER_SHORTCUT.lock().unwrap().take() {
+        app.global_shortcut()
+            .unregister(old)
+            .map_err(|e| AppError::Validation(format!("Failed to unregister shortcut: {}", e)))?;
+        info!("[shortcuts] Layer toggle unbound");
+    }
+    crate::settings::update(|s| s.layer_shortcut = None);
+    Ok(())
+}
+
+/// Shortcuts currently registered by the app.
+#[tauri::command]
+pub fn get_registered_shortcuts() -> Vec<RegisteredShortcut> {
+    LAYER_SHORTCUT
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|s| RegisteredShortcut {
+            accelerator: s.into_string(),
+            action: ACTION_TOGGLE_LAYER.into(),
+        })
+        .collect()
+}
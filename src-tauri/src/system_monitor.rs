@@ -1,13 +1,17 @@
 //! System data collection for widget consumption.
 //!
-//! Provides one-shot and real-time system metrics (CPU, memory, battery, disk, network)
+//! Provides one-shot and real-time system metrics (CPU, memory, battery, disk, network, Wi-Fi)
 //! that the frontend filters per-widget based on manifest permissions.
 
+use crate::commands_core::{
+    push_capped, NetworkFilter, DEFAULT_HISTORY_LENGTH, HISTORY_CATEGORIES,
+};
 use log::{error, info};
 use serde::Serialize;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, LazyLock, Mutex};
-use std::time::Duration;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, LazyLock, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use typeshare::typeshare;
 
 // ============================================================================
@@ -29,6 +33,8 @@ pub struct SystemData {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub network: Option<Vec<NetworkInfo>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub wifi: Option<WifiInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub media: Option<crate::media::MediaInfo>,
 }
 
@@ -40,6 +46,10 @@ pub struct CpuInfo {
     /// CPU usage percentage (0-100)
     pub usage: f32,
     pub model: String,
+    /// Current clock averaged over all cores; `None` when not reported
+    pub frequency_mhz: Option<u64>,
+    /// Rated maximum clock, where the platform exposes it
+    pub max_frequency_mhz: Option<u64>,
 }
 
 #[typeshare]
@@ -52,6 +62,10 @@ pub struct MemoryInfo {
     pub used: u64,
     /// Free memory in bytes
     pub free: u64,
+    /// Swap / page file size in bytes (0 when there is none)
+    pub swap_total: u64,
+    /// Used swap / page file in bytes
+    pub swap_used: u64,
 }
 
 #[typeshare]
@@ -67,6 +81,13 @@ pub struct BatteryInfo {
     /// Estimated seconds until empty, if available
     #[serde(skip_serializing_if = "Option::is_none")]
     pub time_to_empty: Option<u64>,
+    /// Estimated seconds until full while charging, if available
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_to_full: Option<u64>,
+    /// Individual batteries when more than one is present; the outer fields
+    /// are then the combined values.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub batteries: Vec<BatteryInfo>,
 }
 
 #[typeshare]
@@ -80,6 +101,12 @@ pub struct DiskInfo {
     pub available: u64,
     /// Filesystem type (e.g., "NTFS")
     pub fs: String,
+    /// Bytes read per second since the previous poll; `None` on the first one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_per_sec: Option<u64>,
+    /// Bytes written per second since the previous poll; `None` on the first one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub written_per_sec: Option<u64>,
 }
 
 #[typeshare]
@@ -93,13 +120,85 @@ pub struct NetworkInfo {
     pub transmitted: u64,
 }
 
+#[typeshare]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WifiInfo {
+    pub ssid: String,
+    /// Signal quality (0-100)
+    pub signal_percent: u32,
+    /// Frequency band (e.g., "2.4GHz", "5GHz"), if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub band: Option<String>,
+    /// Receive link rate in Mbps
+    pub link_speed_mbps: u32,
+}
+
+/// One buffered poll; `data` holds only the requested category.
+#[typeshare]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricSample {
+    /// Unix time in milliseconds
+    pub timestamp: u64,
+    pub data: SystemData,
+}
+
 // ============================================================================
 // Monitor State
 // ============================================================================
 
 static MONITOR_RUNNING: AtomicBool = AtomicBool::new(false);
+/// Bumped on every start so a thread still sleeping from before a stop exits
+/// instead of running alongside its replacement.
+static MONITOR_GENERATION: AtomicU64 = AtomicU64::new(0);
+/// Handle and interval of the last start, for restarts on resubscribe.
+static MONITOR_CONFIG: OnceLock<(tauri::AppHandle, u64)> = OnceLock::new();
+/// Ticks with nothing to poll before the monitor thread exits on its own.
+const IDLE_TICKS_BEFORE_STOP: u32 = 20;
 static POLL_CATEGORIES: LazyLock<Arc<Mutex<Vec<String>>>> =
     LazyLock::new(|| Arc::new(Mutex::new(Vec::new())));
+/// Set by `refresh_now` to cut the monitor's current sleep short.
+static WAKE_REQUESTED: Mutex<bool> = Mutex::new(false);
+static WAKE: Condvar = Condvar::new();
+
+/// `System` shared by one-shot calls so polling widgets don't pay the CPU
+/// warmup on every call.
+struct WarmSystem {
+    sys: sysinfo::System,
+    last_cpu_refresh: Option<Instant>,
+}
+
+static ONE_SHOT_SYSTEM: LazyLock<Mutex<WarmSystem>> = LazyLock::new(|| {
+    Mutex::new(WarmSystem {
+        sys: sysinfo::System::new(),
+        last_cpu_refresh: None,
+    })
+});
+
+/// A previous CPU refresh younger than this is a valid baseline, so one refresh suffices.
+const CPU_WARM_WINDOW: Duration = Duration::from_secs(30);
+
+/// `Disks` kept across polls so I/O counters have a baseline to diff against.
+struct WarmDisks {
+    disks: sysinfo::Disks,
+    last_refresh: Option<Instant>,
+}
+
+static WARM_DISKS: LazyLock<Mutex<WarmDisks>> = LazyLock::new(|| {
+    Mutex::new(WarmDisks {
+        disks: sysinfo::Disks::new(),
+        last_refresh: None,
+    })
+});
+
+/// Last polls per category, filled only while that category is subscribed.
+static HISTORY: LazyLock<Mutex<HashMap<String, VecDeque<MetricSample>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static HISTORY_LENGTH: AtomicUsize = AtomicUsize::new(DEFAULT_HISTORY_LENGTH);
+
+static NETWORK_FILTER: LazyLock<Mutex<NetworkFilter>> =
+    LazyLock::new(|| Mutex::new(NetworkFilter::default()));
 
 // ============================================================================
 // Data Collection
@@ -107,8 +206,6 @@ static POLL_CATEGORIES: LazyLock<Arc<Mutex<Vec<String>>>> =
 
 /// Collect system data for the requested categories (one-shot).
 pub fn collect_system_data(categories: &[String]) -> SystemData {
-    use sysinfo::System;
-
     let mut data = SystemData::default();
 
     // CPU needs two refreshes with a gap for accurate readings
@@ -117,15 +214,33 @@ pub fn collect_system_data(categories: &[String]) -> SystemData {
     let needs_disk = categories.iter().any(|c| c == "disk");
     let needs_network = categories.iter().any(|c| c == "network");
     let needs_battery = categories.iter().any(|c| c == "battery");
+    let needs_wifi = categories.iter().any(|c| c == "wifi");
     let needs_media = categories.iter().any(|c| c == "media");
 
     if needs_cpu || needs_memory {
-        let mut sys = System::new();
+        let mut warm = ONE_SHOT_SYSTEM.lock().unwrap();
+        let WarmSystem {
+            sys,
+            last_cpu_refresh,
+        } = &mut *warm;
 
         if needs_cpu {
-            sys.refresh_cpu_usage();
-            std::thread::sleep(Duration::from_millis(200));
-            sys.refresh_cpu_usage();
+            // Usage is a delta between two refreshes: warm up with a gap when
+            // there's no recent baseline, and reuse the last reading when the
+            // baseline is too young to give a meaningful delta
+            match last_cpu_refresh.map(|t| t.elapsed()) {
+                Some(age) if age < sysinfo::MINIMUM_CPU_UPDATE_INTERVAL => {}
+                Some(age) if age < CPU_WARM_WINDOW => {
+                    sys.refresh_cpu_all();
+                    *last_cpu_refresh = Some(Instant::now());
+                }
+                _ => {
+                    sys.refresh_cpu_usage();
+                    std::thread::sleep(Duration::from_millis(200));
+                    sys.refresh_cpu_all();
+                    *last_cpu_refresh = Some(Instant::now());
+                }
+            }
 
             let cpus = sys.cpus();
             let usage: f32 = if cpus.is_empty() {
@@ -142,6 +257,8 @@ pub fn collect_system_data(categories: &[String]) -> SystemData {
                 cores: cpus.len() as u32,
                 usage,
                 model,
+                frequency_mhz: average_frequency_mhz(cpus),
+                max_frequency_mhz: max_cpu_frequency_mhz(),
             });
         }
 
@@ -151,43 +268,28 @@ pub fn collect_system_data(categories: &[String]) -> SystemData {
                 total: sys.total_memory(),
                 used: sys.used_memory(),
                 free: sys.available_memory(),
+                swap_total: sys.total_swap(),
+                swap_used: sys.used_swap().min(sys.total_swap()),
             });
         }
     }
 
     if needs_disk {
-        let disks = sysinfo::Disks::new_with_refreshed_list();
-        data.disk = Some(
-            disks
-                .iter()
-                .map(|d| DiskInfo {
-                    name: d.name().to_string_lossy().to_string(),
-                    total: d.total_space(),
-                    available: d.available_space(),
-                    fs: d.file_system().to_string_lossy().to_string(),
-                })
-                .collect(),
-        );
+        data.disk = Some(collect_disk_info());
     }
 
     if needs_network {
-        let networks = sysinfo::Networks::new_with_refreshed_list();
-        data.network = Some(
-            networks
-                .iter()
-                .map(|(name, net)| NetworkInfo {
-                    name: name.clone(),
-                    received: net.total_received(),
-                    transmitted: net.total_transmitted(),
-                })
-                .collect(),
-        );
+        data.network = Some(collect_network_info());
     }
 
     if needs_battery {
         data.battery = collect_battery_info();
     }
 
+    if needs_wifi {
+        data.wifi = collect_wifi_info();
+    }
+
     if needs_media {
         data.media = crate::media::get_media_info().ok();
     }
@@ -195,18 +297,242 @@ pub fn collect_system_data(categories: &[String]) -> SystemData {
     data
 }
 
+/// Space and I/O rates per disk. Rates need a previous refresh, so they are
+/// `None` on the first call.
+fn collect_disk_info() -> Vec<DiskInfo> {
+    let mut warm = WARM_DISKS.lock().unwrap();
+    let WarmDisks {
+        disks,
+        last_refresh,
+    } = &mut *warm;
+
+    let elapsed = last_refresh.map(|t| t.elapsed().as_secs_f64());
+    disks.refresh(true);
+    *last_refresh = Some(Instant::now());
+
+    let per_sec = |bytes: u64| {
+        elapsed
+            .filter(|&e| e > 0.0)
+            .map(|e| (bytes as f64 / e) as u64)
+    };
+    disks
+        .iter()
+        .map(|d| {
+            let usage = d.usage();
+            DiskInfo {
+                name: d.name().to_string_lossy().to_string(),
+                total: d.total_space(),
+                available: d.available_space(),
+                fs: d.file_system().to_string_lossy().to_string(),
+                read_per_sec: per_sec(usage.read_bytes),
+                written_per_sec: per_sec(usage.written_bytes),
+            }
+        })
+        .collect()
+}
+
+/// Composite 0.0-1.0 load from CPU, memory and disk I/O; see
+/// `commands_core::system_pressure` for the weighting. Disk I/O contributes
+/// from the second call on, once there is a baseline.
+pub fn system_pressure() -> f32 {
+    let categories = ["cpu", "memory", "disk"].map(String::from);
+    let data = collect_system_data(&categories);
+    let cpu = data.cpu.map_or(0.0, |c| c.usage);
+    let memory = data
+        .memory
+        .filter(|m| m.total > 0)
+        .map_or(0.0, |m| m.used as f32 / m.total as f32);
+    let disk_io = data
+        .disk
+        .unwrap_or_default()
+        .iter()
+        .map(|d| d.read_per_sec.unwrap_or(0) + d.written_per_sec.unwrap_or(0))
+        .sum();
+    crate::commands_core::system_pressure(cpu, memory, disk_io)
+}
+
+/// Mean current clock of `cpus`; sysinfo reports 0 when it can't read it.
+fn average_frequency_mhz(cpus: &[sysinfo::Cpu]) -> Option<u64> {
+    let known: Vec<u64> = cpus
+        .iter()
+        .map(|c| c.frequency())
+        .filter(|&f| f > 0)
+        .collect();
+    if known.is_empty() {
+        None
+    } else {
+        Some(known.iter().sum::<u64>() / known.len() as u64)
+    }
+}
+
+/// Rated maximum clock. Fixed for the session, so read once.
+fn max_cpu_frequency_mhz() -> Option<u64> {
+    static MAX: OnceLock<Option<u64>> = OnceLock::new();
+    *MAX.get_or_init(read_max_cpu_frequency_mhz)
+}
+
+#[cfg(target_os = "windows")]
+fn read_max_cpu_frequency_mhz() -> Option<u64> {
+    use windows::Win32::System::Power::{
+        CallNtPowerInformation, ProcessorInformation, PROCESSOR_POWER_INFORMATION,
+    };
+    use windows::Win32::System::Threading::{GetActiveProcessorCount, ALL_PROCESSOR_GROUPS};
+
+    // One entry per logical processor in every group; available_parallelism
+    // is capped by affinity and job limits, which makes the call fail
+    let count = unsafe { GetActiveProcessorCount(ALL_PROCESSOR_GROUPS) }.max(1) as usize;
+    let mut infos = vec![PROCESSOR_POWER_INFORMATION::default(); count];
+    let size = std::mem::size_of_val(infos.as_slice()) as u32;
+    unsafe {
+        CallNtPowerInformation(
+            ProcessorInformation,
+            None,
+            0,
+            Some(infos.as_mut_ptr() as *mut _),
+            size,
+        )
+        .ok()
+        .ok()?;
+    }
+    infos
+        .iter()
+        .map(|i| i.MaxMhz as u64)
+        .max()
+        .filter(|&m| m > 0)
+}
+
+#[cfg(target_os = "linux")]
+fn read_max_cpu_frequency_mhz() -> Option<u64> {
+    // Reported in kHz
+    std::fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq")
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(|khz| khz / 1000)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn read_max_cpu_frequency_mhz() -> Option<u64> {
+    None
+}
+
+/// Network interfaces that pass the current `NetworkFilter`.
+fn collect_network_info() -> Vec<NetworkInfo> {
+    let filter = NETWORK_FILTER.lock().unwrap().clone();
+    let connectors = match filter {
+        NetworkFilter::Physical => physical_interface_names(),
+        _ => None,
+    };
+    let networks = sysinfo::Networks::new_with_refreshed_list();
+    networks
+        .iter()
+        .filter(|(name, _)| {
+            let physical = connectors.as_ref().map(|c| c.contains(name.as_str()));
+            filter.allows(name, physical)
+        })
+        .map(|(name, net)| NetworkInfo {
+            name: name.clone(),
+            received: net.total_received(),
+            transmitted: net.total_transmitted(),
+        })
+        .collect()
+}
+
+/// Aliases of adapters with a physical connector, the same test as
+/// `Get-NetAdapter -Physical`. `None` if the table can't be read.
+#[cfg(target_os = "windows")]
+fn physical_interface_names() -> Option<std::collections::HashSet<String>> {
+    use windows::Win32::NetworkManagement::IpHelper::{FreeMibTable, GetIfTable2, MIB_IF_TABLE2};
+
+    // Bit 2 of InterfaceAndOperStatusFlags (HardwareInterface, FilterInterface, ConnectorPresent, ...)
+    const CONNECTOR_PRESENT: u8 = 1 << 2;
+
+    unsafe {
+        let mut table: *mut MIB_IF_TABLE2 = std::ptr::null_mut();
+        if GetIfTable2(&mut table).is_err() || table.is_null() {
+            return None;
+        }
+        let rows =
+            std::slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize);
+        let names = rows
+            .iter()
+            .filter(|r| r.InterfaceAndOperStatusFlags._bitfield & CONNECTOR_PRESENT != 0)
+            .map(|r| {
+                let len = r
+                    .Alias
+                    .iter()
+                    .position(|&c| c == 0)
+                    .unwrap_or(r.Alias.len());
+                String::from_utf16_lossy(&r.Alias[..len])
+            })
+            .collect();
+        FreeMibTable(table as *const _);
+        Some(names)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn physical_interface_names() -> Option<std::collections::HashSet<String>> {
+    None
+}
+
 /// Collect battery info. Returns None on desktops without a battery.
+/// Multiple batteries are combined by energy, with each one listed in `batteries`.
 fn collect_battery_info() -> Option<BatteryInfo> {
+    use battery::units::{energy::watt_hour, power::watt};
+    use battery::State;
+
     let manager = battery::Manager::new().ok()?;
-    let mut batteries = manager.batteries().ok()?;
-    let batt = batteries.next()?.ok()?;
+    let batts: Vec<battery::Battery> = manager.batteries().ok()?.flatten().collect();
+
+    match batts.as_slice() {
+        [] => None,
+        [single] => Some(battery_info(single)),
+        all => {
+            let sum = |f: fn(&battery::Battery) -> f32| all.iter().map(f).sum::<f32>();
+            let energy = sum(|b| b.energy().get::<watt_hour>());
+            let full = sum(|b| b.energy_full().get::<watt_hour>());
+            let design = sum(|b| b.energy_full_design().get::<watt_hour>());
+            let rate = sum(|b| b.energy_rate().get::<watt>());
+            let charging = all
+                .iter()
+                .any(|b| matches!(b.state(), State::Charging | State::Full));
+            let discharging = all.iter().any(|b| b.state() == State::Discharging);
+
+            // Batteries may drain one after another, so per-battery ETAs don't add
+            // up; derive the combined ETA from total energy and total power draw.
+            let hours_to = |wh: f32| (rate > 0.0).then(|| (wh / rate * 3600.0) as u64);
+            let time_to_empty = if discharging { hours_to(energy) } else { None };
+            let time_to_full = if charging && !discharging {
+                hours_to(full - energy)
+            } else {
+                None
+            };
+            Some(BatteryInfo {
+                level: if full > 0.0 { energy / full } else { 0.0 },
+                charging,
+                health: (design > 0.0).then(|| full / design),
+                time_to_empty,
+                time_to_full,
+                batteries: all.iter().map(battery_info).collect(),
+            })
+        }
+    }
+}
 
+fn battery_info(batt: &battery::Battery) -> BatteryInfo {
+    use battery::units::{energy::watt_hour, ratio::ratio, time::second};
     use battery::State;
-    let charging = matches!(batt.state(), State::Charging | State::Full);
 
-    use battery::units::{energy::watt_hour, ratio::ratio, time::second};
+    let charging = matches!(batt.state(), State::Charging | State::Full);
 
     let time_to_empty = batt.time_to_empty().map(|t| t.get::<second>() as u64);
+    let time_to_full = if batt.state() == State::Charging {
+        batt.time_to_full().map(|t| t.get::<second>() as u64)
+    } else {
+        None
+    };
 
     let health = {
         let full = batt.energy_full().get::<watt_hour>();
@@ -218,12 +544,134 @@ fn collect_battery_info() -> Option<BatteryInfo> {
         }
     };
 
-    Some(BatteryInfo {
+    BatteryInfo {
         level: batt.state_of_charge().get::<ratio>(),
         charging,
         health,
         time_to_empty,
-    })
+        time_to_full,
+        batteries: Vec::new(),
+    }
+}
+
+/// Collect the connected Wi-Fi link. Returns None without a connected wireless adapter.
+#[cfg(target_os = "windows")]
+fn collect_wifi_info() -> Option<WifiInfo> {
+    use windows::Win32::Foundation::HANDLE;
+    use windows::Win32::NetworkManagement::WiFi::*;
+
+    /// Closes the WLAN client handle on every exit path.
+    struct WlanHandle(HANDLE);
+    impl Drop for WlanHandle {
+        fn drop(&mut self) {
+            unsafe {
+                WlanCloseHandle(self.0, None);
+            }
+        }
+    }
+
+    unsafe {
+        let mut negotiated = 0u32;
+        let mut raw = HANDLE::default();
+        // Client version 2 = Vista and later
+        if WlanOpenHandle(2, None, &mut negotiated, &mut raw) != 0 {
+            return None;
+        }
+        let handle = WlanHandle(raw);
+
+        let mut list: *mut WLAN_INTERFACE_INFO_LIST = std::ptr::null_mut();
+        if WlanEnumInterfaces(handle.0, None, &mut list) != 0 || list.is_null() {
+            return None;
+        }
+        let interfaces = std::slice::from_raw_parts(
+            (*list).InterfaceInfo.as_ptr(),
+            (*list).dwNumberOfItems as usize,
+        );
+
+        let result = interfaces
+            .iter()
+            .filter(|i| i.isState == wlan_interface_state_connected)
+            .find_map(|i| {
+                let query = |opcode: WLAN_INTF_OPCODE| -> Option<*mut std::ffi::c_void> {
+                    let mut size = 0u32;
+                    let mut data: *mut std::ffi::c_void = std::ptr::null_mut();
+                    let rc = WlanQueryInterface(
+                        handle.0,
+                        &i.InterfaceGuid,
+                        opcode,
+                        None,
+                        &mut size,
+                        &mut data,
+                        None,
+                    );
+                    (rc == 0 && !data.is_null()).then_some(data)
+                };
+
+                let conn = query(wlan_intf_opcode_current_connection)?;
+                let attrs =
+                    &(*(conn as *const WLAN_CONNECTION_ATTRIBUTES)).wlanAssociationAttributes;
+                let ssid_len = (attrs.dot11Ssid.uSSIDLength as usize).min(32);
+                let info = WifiInfo {
+                    ssid: String::from_utf8_lossy(&attrs.dot11Ssid.ucSSID[..ssid_len]).into_owned(),
+                    signal_percent: attrs.wlanSignalQuality.min(100),
+                    band: None,
+                    // Reported in kbps
+                    link_speed_mbps: attrs.ulRxRate / 1000,
+                };
+                WlanFreeMemory(conn);
+
+                let band = query(wlan_intf_opcode_channel_number).map(|ch| {
+                    let channel = *(ch as *const u32);
+                    WlanFreeMemory(ch);
+                    // 6 GHz reuses low channel numbers and can't be told apart here
+                    if channel <= 14 { "2.4GHz" } else { "5GHz" }.to_string()
+                });
+                Some(WifiInfo { band, ..info })
+            });
+
+        WlanFreeMemory(list as *const _);
+        result
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn collect_wifi_info() -> Option<WifiInfo> {
+    None
+}
+
+/// Whether this process runs elevated (as admin). Explorer normally doesn't,
+/// and UIPI then blocks some of the messages WorkerW injection relies on.
+#[cfg(target_os = "windows")]
+pub fn is_elevated() -> bool {
+    use windows::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows::Win32::Security::{
+        GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY,
+    };
+    use windows::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+    unsafe {
+        let mut token = HANDLE::default();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token).is_err() {
+            return false;
+        }
+        let mut elevation = TOKEN_ELEVATION::default();
+        let mut len = 0u32;
+        let ok = GetTokenInformation(
+            token,
+            TokenElevation,
+            Some(&mut elevation as *mut _ as *mut _),
+            std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut len,
+        )
+        .is_ok();
+        let _ = CloseHandle(token);
+        ok && elevation.TokenIsElevated != 0
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn is_elevated() -> bool {
+    false
 }
 
 // ============================================================================
@@ -239,10 +687,12 @@ fn collect_with_system(sys: &mut sysinfo::System, categories: &[String]) -> Syst
     let needs_disk = categories.iter().any(|c| c == "disk");
     let needs_network = categories.iter().any(|c| c == "network");
     let needs_battery = categories.iter().any(|c| c == "battery");
+    let needs_wifi = categories.iter().any(|c| c == "wifi");
     let needs_media = categories.iter().any(|c| c == "media");
 
     if needs_cpu {
-        sys.refresh_cpu_usage();
+        // Frequency rides along with usage, so no extra refresh or sleep
+        sys.refresh_cpu_all();
 
         let cpus = sys.cpus();
         let usage: f32 = if cpus.is_empty() {
@@ -259,6 +709,8 @@ fn collect_with_system(sys: &mut sysinfo::System, categories: &[String]) -> Syst
             cores: cpus.len() as u32,
             usage,
             model,
+            frequency_mhz: average_frequency_mhz(cpus),
+            max_frequency_mhz: max_cpu_frequency_mhz(),
         });
     }
 
@@ -268,42 +720,27 @@ fn collect_with_system(sys: &mut sysinfo::System, categories: &[String]) -> Syst
             total: sys.total_memory(),
             used: sys.used_memory(),
             free: sys.available_memory(),
+            swap_total: sys.total_swap(),
+            swap_used: sys.used_swap().min(sys.total_swap()),
         });
     }
 
     if needs_disk {
-        let disks = sysinfo::Disks::new_with_refreshed_list();
-        data.disk = Some(
-            disks
-                .iter()
-                .map(|d| DiskInfo {
-                    name: d.name().to_string_lossy().to_string(),
-                    total: d.total_space(),
-                    available: d.available_space(),
-                    fs: d.file_system().to_string_lossy().to_string(),
-                })
-                .collect(),
-        );
+        data.disk = Some(collect_disk_info());
     }
 
     if needs_network {
-        let networks = sysinfo::Networks::new_with_refreshed_list();
-        data.network = Some(
-            networks
-                .iter()
-                .map(|(name, net)| NetworkInfo {
-                    name: name.clone(),
-                    received: net.total_received(),
-                    transmitted: net.total_transmitted(),
-                })
-                .collect(),
-        );
+        data.network = Some(collect_network_info());
     }
 
     if needs_battery {
         data.battery = collect_battery_info();
     }
 
+    if needs_wifi {
+        data.wifi = collect_wifi_info();
+    }
+
     if needs_media {
         data.media = crate::media::get_media_info().ok();
     }
@@ -314,10 +751,12 @@ fn collect_with_system(sys: &mut sysinfo::System, categories: &[String]) -> Syst
 /// Start the background system monitor thread.
 /// Polls at `interval_secs` and emits `system-data-update` events.
 pub fn start_monitor(app_handle: tauri::AppHandle, interval_secs: u64) {
+    let _ = MONITOR_CONFIG.set((app_handle.clone(), interval_secs));
     if MONITOR_RUNNING.swap(true, Ordering::SeqCst) {
         info!("[system_monitor] Monitor already running");
         return;
     }
+    let generation = MONITOR_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
 
     info!(
         "[system_monitor] Starting background monitor ({}s interval)",
@@ -332,39 +771,183 @@ pub fn start_monitor(app_handle: tauri::AppHandle, interval_secs: u64) {
         sys.refresh_cpu_usage();
 
         let interval = Duration::from_secs(interval_secs);
+        let mut idle_ticks = 0;
 
-        while MONITOR_RUNNING.load(Ordering::SeqCst) {
+        while MONITOR_RUNNING.load(Ordering::SeqCst)
+            && MONITOR_GENERATION.load(Ordering::SeqCst) == generation
+        {
             let categories = POLL_CATEGORIES.lock().unwrap().clone();
-
-            if categories.is_empty() {
-                // Rien à poller, on dort et on réessaie
-                std::thread::sleep(interval);
+            let tray_stats = crate::tray::stats_enabled();
+
+            if categories.is_empty() && !tray_stats {
+                // Nothing to poll; after a while drop the thread; a new
+                // subscription or the tray stats toggle restarts it
+                idle_ticks += 1;
+                if idle_ticks >= IDLE_TICKS_BEFORE_STOP {
+                    if MONITOR_GENERATION.load(Ordering::SeqCst) == generation {
+                        MONITOR_RUNNING.store(false, Ordering::SeqCst);
+                    }
+                    // A subscription that raced the stop saw us still running
+                    if !POLL_CATEGORIES.lock().unwrap().is_empty() || crate::tray::stats_enabled() {
+                        ensure_running();
+                    }
+                    break;
+                }
+                sleep_or_wake(interval);
                 continue;
             }
+            idle_ticks = 0;
+
+            // The tray tooltip piggybacks on this thread instead of owning a sysinfo instance
+            let mut wanted = categories.clone();
+            if tray_stats {
+                for c in ["cpu", "memory"] {
+                    if !wanted.iter().any(|w| w == c) {
+                        wanted.push(c.to_string());
+                    }
+                }
+            }
 
-            let data = collect_with_system(&mut sys, &categories);
+            let mut data = collect_with_system(&mut sys, &wanted);
 
-            let event = AppEvent::SystemDataUpdate(Box::new(data));
-            if let Err(e) = app_handle.emit_app_event(&event) {
-                error!("[system_monitor] Failed to emit event: {}", e);
+            if tray_stats {
+                crate::tray::update_stats_tooltip(&app_handle, &data);
             }
 
-            std::thread::sleep(interval);
+            if !categories.is_empty() {
+                record_history(&categories, &data);
+                // Don't leak tray-only categories to the frontend
+                if !categories.iter().any(|c| c == "cpu") {
+                    data.cpu = None;
+                }
+                if !categories.iter().any(|c| c == "memory") {
+                    data.memory = None;
+                }
+                let event = AppEvent::SystemDataUpdate(Box::new(data));
+                if let Err(e) = app_handle.emit_app_event(&event) {
+                    error!("[system_monitor] Failed to emit event: {}", e);
+                }
+            }
+
+            sleep_or_wake(interval);
         }
 
         info!("[system_monitor] Monitor stopped");
     });
 }
 
-/// Stop the background monitor.
-#[allow(dead_code)]
+/// Sleep for `interval`, or less if `refresh_now` asks for a poll.
+fn sleep_or_wake(interval: Duration) {
+    let guard = WAKE_REQUESTED.lock().unwrap();
+    let (mut requested, _) = WAKE
+        .wait_timeout_while(guard, interval, |requested| !*requested)
+        .unwrap();
+    *requested = false;
+}
+
+// ============================================================================
+// Metric History
+// ============================================================================
+
+/// Buffer one poll for each subscribed history category.
+fn record_history(categories: &[String], data: &SystemData) {
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let cap = HISTORY_LENGTH.load(Ordering::Relaxed);
+    let mut history = HISTORY.lock().unwrap();
+    for category in categories {
+        if !HISTORY_CATEGORIES.contains(&category.as_str()) {
+            continue;
+        }
+        let sample = MetricSample {
+            timestamp,
+            data: category_only(data, category),
+        };
+        push_capped(history.entry(category.clone()).or_default(), sample, cap);
+    }
+}
+
+/// A copy of `data` with every category but `category` cleared.
+fn category_only(data: &SystemData, category: &str) -> SystemData {
+    let mut out = SystemData::default();
+    match category {
+        "cpu" => out.cpu = data.cpu.clone(),
+        "memory" => out.memory = data.memory.clone(),
+        "battery" => out.battery = data.battery.clone(),
+        "disk" => out.disk = data.disk.clone(),
+        "network" => out.network = data.network.clone(),
+        "wifi" => out.wifi = data.wifi.clone(),
+        _ => {}
+    }
+    out
+}
+
+/// Buffered samples for `category`, oldest first.
+pub fn get_history(category: &str) -> Vec<MetricSample> {
+    HISTORY
+        .lock()
+        .unwrap()
+        .get(category)
+        .map(|buf| buf.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Change how many samples each category keeps, trimming existing buffers.
+pub fn set_history_length(n: usize) {
+    HISTORY_LENGTH.store(n, Ordering::Relaxed);
+    for buf in HISTORY.lock().unwrap().values_mut() {
+        let excess = buf.len().saturating_sub(n);
+        buf.drain(..excess);
+    }
+    info!("[system_monitor] History length: {}", n);
+}
+
+/// Stop the background monitor. It can be started again at any time.
 pub fn stop_monitor() {
     MONITOR_RUNNING.store(false, Ordering::SeqCst);
     info!("[system_monitor] Stop requested");
 }
 
+/// Restart the monitor with its last settings if it stopped; no-op before the
+/// first `start_monitor`.
+pub fn ensure_running() {
+    if MONITOR_RUNNING.load(Ordering::SeqCst) {
+        return;
+    }
+    if let Some((app, interval_secs)) = MONITOR_CONFIG.get() {
+        start_monitor(app.clone(), *interval_secs);
+    }
+}
+
+/// Poll the subscribed categories now instead of at the next tick, so a
+/// freshly mounted widget fills in right away. A stopped monitor is restarted,
+/// which polls immediately. No-op with nothing subscribed.
+pub fn refresh_now() {
+    if POLL_CATEGORIES.lock().unwrap().is_empty() {
+        return;
+    }
+    if !MONITOR_RUNNING.load(Ordering::SeqCst) {
+        ensure_running();
+        return;
+    }
+    *WAKE_REQUESTED.lock().unwrap() = true;
+    WAKE.notify_all();
+}
+
+/// Choose which network interfaces are reported; see `NetworkFilter`.
+pub fn set_network_filter(filter: NetworkFilter) {
+    info!("[system_monitor] Network filter: {:?}", filter);
+    *NETWORK_FILTER.lock().unwrap() = filter;
+}
+
 /// Update the categories the monitor polls. Pass empty to pause polling.
 pub fn set_poll_categories(categories: Vec<String>) {
     info!("[system_monitor] Poll categories updated: {:?}", categories);
+    let resume = !categories.is_empty();
     *POLL_CATEGORIES.lock().unwrap() = categories;
+    if resume {
+        ensure_running();
+    }
 }
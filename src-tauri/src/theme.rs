@@ -0,0 +1,104 @@
+//! System theme — light/dark mode and accent color for wallpapers that follow
+//! the OS look.
+//!
+//! Read from the personalization registry keys. The window layer's dispatch
+//! window calls `refresh` on the "ImmersiveColorSet" `WM_SETTINGCHANGE`, which
+//! Windows broadcasts for both dark mode and accent changes.
+
+use crate::commands_core::RgbColor;
+use crate::events::{AppEvent, EmitAppEvent};
+use log::info;
+use std::sync::Mutex;
+use typeshare::typeshare;
+
+/// Windows' stock accent blue, used when the registry value is missing.
+const DEFAULT_ACCENT: RgbColor = RgbColor {
+    r: 0x00,
+    g: 0x78,
+    b: 0xD4,
+};
+
+/// Last theme emitted, so the frequent broadcast only emits real changes.
+static LAST: Mutex<Option<SystemTheme>> = Mutex::new(None);
+
+#[typeshare]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemTheme {
+    /// Apps use dark mode.
+    pub dark: bool,
+    pub accent: RgbColor,
+}
+
+/// Current theme, read fresh. Light mode with the stock accent when the keys
+/// are unreadable.
+pub fn get() -> SystemTheme {
+    read_theme()
+}
+
+/// Record the startup theme, so the first broadcast only emits if it changed.
+pub fn init() {
+    *LAST.lock().unwrap() = Some(read_theme());
+}
+
+/// Re-read the theme and emit `theme-changed` if it differs from the last one.
+pub fn refresh(app: &tauri::AppHandle) {
+    let theme = read_theme();
+    if LAST.lock().unwrap().replace(theme) != Some(theme) {
+        info!(
+            "[theme] Dark: {}, accent: #{:02X}{:02X}{:02X}",
+            theme.dark, theme.accent.r, theme.accent.g, theme.accent.b
+        );
+        let _ = app.emit_app_event(&AppEvent::ThemeChanged(theme));
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn read_theme() -> SystemTheme {
+    use windows::core::w;
+
+    let light = read_dword(
+        w!("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize"),
+        w!("AppsUseLightTheme"),
+    );
+    // Stored as 0xAABBGGRR
+    let accent =
+        read_dword(w!("Software\\Microsoft\\Windows\\DWM"), w!("AccentColor")).map(|v| RgbColor {
+            r: v as u8,
+            g: (v >> 8) as u8,
+            b: (v >> 16) as u8,
+        });
+    SystemTheme {
+        dark: light == Some(0),
+        accent: accent.unwrap_or(DEFAULT_ACCENT),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn read_dword(key: windows::core::PCWSTR, value: windows::core::PCWSTR) -> Option<u32> {
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+
+    let mut data = 0u32;
+    let mut size = std::mem::size_of::<u32>() as u32;
+    unsafe {
+        RegGetValueW(
+            HKEY_CURRENT_USER,
+            key,
+            value,
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut data as *mut u32 as *mut _),
+            Some(&mut size),
+        )
+    }
+    .is_ok()
+    .then_some(data)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_theme() -> SystemTheme {
+    SystemTheme {
+        dark: false,
+        accent: DEFAULT_ACCENT,
+    }
+}
@@ -1,31 +1,119 @@
-//! System tray — quit.
+//! System tray — desktop icons toggle, pause, monitor picker, live stats
+//! tooltip, logs, quit.
 
+use crate::commands_core::TrayIconState;
 use log::{error, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 use tauri::{
     image::Image,
-    menu::{MenuBuilder, MenuItemBuilder},
-    tray::{TrayIconBuilder, TrayIconEvent},
-    AppHandle, Manager,
+    menu::{
+        CheckMenuItem, CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder, Submenu, SubmenuBuilder,
+    },
+    tray::{TrayIcon, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Manager, Wry,
 };
 
-pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
-    let icon = Image::from_bytes(include_bytes!("../icons/32x32.png")).unwrap_or_else(|_| {
+/// User-requested pause: the frontend halts its animation loop, but the window
+/// stays injected in WorkerW and the mouse hook keeps running.
+static WALLPAPER_PAUSED: AtomicBool = AtomicBool::new(false);
+/// Opt-in CPU/RAM tooltip, refreshed by the system monitor thread.
+static TRAY_STATS: AtomicBool = AtomicBool::new(false);
+/// Icon state set from the frontend; `Active` lets visibility drive the icon.
+static ICON_OVERRIDE: Mutex<TrayIconState> = Mutex::new(TrayIconState::Active);
+/// Last combined visibility from `emit_visibility`.
+static WALLPAPER_VISIBLE: AtomicBool = AtomicBool::new(true);
+
+const DEFAULT_TOOLTIP: &str = "MyWallpaper Desktop";
+/// Menu id prefix for monitor picker entries; the suffix is an index or "all".
+const MONITOR_ID_PREFIX: &str = "monitor:";
+
+/// Tray handles whose state must follow changes made outside the tray.
+struct TrayItems {
+    tray: TrayIcon<Wry>,
+    show_icons: CheckMenuItem<Wry>,
+    monitors: Submenu<Wry>,
+}
+
+/// The embedded icon, or a plain square if it fails to decode.
+fn base_icon() -> Image<'static> {
+    Image::from_bytes(include_bytes!("../icons/32x32.png")).unwrap_or_else(|_| {
         error!("[tray] Failed to load icon, using fallback.");
         Image::new_owned(vec![255u8; 32 * 32 * 4], 32, 32)
-    });
+    })
+}
+
+fn icon_for(state: TrayIconState) -> Image<'static> {
+    let base = base_icon();
+    if state == TrayIconState::Active {
+        return base;
+    }
+    Image::new_owned(state.tint(base.rgba()), base.width(), base.height())
+}
+
+pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let icon = base_icon();
 
+    let show_icons_item = CheckMenuItemBuilder::with_id("show_icons", "Show desktop icons")
+        .checked(crate::settings::get().show_desktop_icons)
+        .build(app)?;
+    let pause_item = CheckMenuItemBuilder::with_id("pause", "Pause wallpaper")
+        .checked(false)
+        .build(app)?;
+    let monitors = SubmenuBuilder::new(app, "Wallpaper monitor").build()?;
+    populate_monitor_menu(app, &monitors);
+    let logs_item = MenuItemBuilder::with_id("open_logs", "Open logs folder").build(app)?;
     let quit_item = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
-    let menu = MenuBuilder::new(app).item(&quit_item).build()?;
+    let menu = MenuBuilder::new(app)
+        .item(&show_icons_item)
+        .item(&pause_item)
+        .item(&monitors)
+        .separator()
+        .item(&logs_item)
+        .item(&quit_item)
+        .build()?;
+
+    let show_icons = show_icons_item.clone();
+    TRAY_STATS.store(crate::settings::get().tray_stats, Ordering::SeqCst);
 
-    let _tray = TrayIconBuilder::new()
+    let tray = TrayIconBuilder::new()
         .icon(icon)
-        .tooltip("MyWallpaper Desktop")
+        .tooltip(DEFAULT_TOOLTIP)
         .menu(&menu)
-        .on_menu_event(move |app, event| {
-            if event.id().as_ref() == "quit" {
+        .on_menu_event(move |app, event| match event.id().as_ref() {
+            "show_icons" => {
+                // The check state is toggled natively before this callback runs
+                let visible = show_icons_item.is_checked().unwrap_or(true);
+                crate::window_layer::apply_desktop_icons_visible(visible);
+                crate::settings::update(|s| s.show_desktop_icons = visible);
+            }
+            "pause" => {
+                let paused = !WALLPAPER_PAUSED.fetch_xor(true, Ordering::SeqCst);
+                let _ = pause_item.set_checked(paused);
+                info!(
+                    "[tray] Wallpaper {}",
+                    if paused { "paused" } else { "resumed" }
+                );
+                emit_visibility(app);
+            }
+            "open_logs" => {
+                if let Err(e) = crate::commands::open_logs_folder(app.clone()) {
+                    error!("[tray] Failed to open logs folder: {}", e);
+                }
+            }
+            "quit" => {
                 crate::window_layer::restore_desktop_icons_and_unhook();
                 app.exit(0);
             }
+            id if id.starts_with(MONITOR_ID_PREFIX) => {
+                let index = id[MONITOR_ID_PREFIX.len()..].parse::<usize>().ok();
+                if let Err(e) = crate::window_layer::set_wallpaper_monitor(app.clone(), index) {
+                    error!("[tray] Failed to switch monitor: {}", e);
+                    // Undo the native check toggle
+                    rebuild_monitor_menu(app);
+                }
+            }
+            _ => {}
         })
         .on_tray_icon_event(|tray, event| {
             if let TrayIconEvent::Click {
@@ -41,6 +129,139 @@ pub fn setup_tray(app: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
         })
         .build(app)?;
 
+    app.manage(TrayItems {
+        tray,
+        show_icons,
+        monitors,
+    });
+
     info!("[tray] System tray ready.");
     Ok(())
 }
+
+/// Reflect a desktop icons visibility change made outside the tray (e.g. from the frontend).
+pub fn sync_desktop_icons_checked(app: &AppHandle, visible: bool) {
+    if let Some(items) = app.try_state::<TrayItems>() {
+        let _ = items.show_icons.set_checked(visible);
+    }
+}
+
+/// Re-list monitors and checkmarks after a display change or a selection.
+pub fn rebuild_monitor_menu(app: &AppHandle) {
+    if let Some(items) = app.try_state::<TrayItems>() {
+        populate_monitor_menu(app, &items.monitors);
+    }
+}
+
+fn populate_monitor_menu(app: &AppHandle, submenu: &Submenu<Wry>) {
+    if let Ok(old) = submenu.items() {
+        for item in old {
+            let _ = submenu.remove(&item);
+        }
+    }
+    let selected = crate::window_layer::wallpaper_monitor();
+    let entry = |id: String, label: String, checked: bool| {
+        CheckMenuItemBuilder::with_id(id, label)
+            .checked(checked)
+            .build(app)
+    };
+    if let Ok(item) = entry(
+        format!("{}all", MONITOR_ID_PREFIX),
+        "All monitors".into(),
+        selected.is_none(),
+    ) {
+        let _ = submenu.append(&item);
+    }
+    for m in crate::window_layer::get_monitors() {
+        let label = format!(
+            "{}. {} ({}x{}){}",
+            m.index + 1,
+            m.name.trim_start_matches(r"\\.\"),
+            m.width,
+            m.height,
+            if m.is_primary { " — primary" } else { "" }
+        );
+        let checked = selected == Some(m.index as usize);
+        if let Ok(item) = entry(format!("{}{}", MONITOR_ID_PREFIX, m.index), label, checked) {
+            let _ = submenu.append(&item);
+        }
+    }
+}
+
+/// Emit the combined wallpaper visibility: hidden while the user paused it
+/// from the tray, while idle, or while a fullscreen/listed app is in the
+/// foreground. Every hide/show source goes through here.
+pub fn emit_visibility(app: &AppHandle) {
+    use crate::events::{AppEvent, EmitAppEvent};
+    let visible = !WALLPAPER_PAUSED.load(Ordering::SeqCst)
+        && !crate::idle::is_idle()
+        && !crate::window_layer::foreground_paused();
+    let _ = app.emit_app_event(&AppEvent::WallpaperVisibility { visible });
+    if WALLPAPER_VISIBLE.swap(visible, Ordering::SeqCst) != visible {
+        refresh_icon(app);
+        crate::frame_rate::refresh(app);
+    }
+}
+
+/// Combined visibility as of the last `emit_visibility`.
+pub fn wallpaper_visible() -> bool {
+    WALLPAPER_VISIBLE.load(Ordering::SeqCst)
+}
+
+/// Set the tray icon state from the frontend. "error" and "paused" stick until
+/// "active" hands control back to the automatic pause/visibility state.
+pub fn set_icon_state(app: &AppHandle, state: TrayIconState) {
+    *ICON_OVERRIDE.lock().unwrap() = state;
+    refresh_icon(app);
+}
+
+fn refresh_icon(app: &AppHandle) {
+    let state = match *ICON_OVERRIDE.lock().unwrap() {
+        TrayIconState::Active if !WALLPAPER_VISIBLE.load(Ordering::SeqCst) => TrayIconState::Paused,
+        state => state,
+    };
+    if let Some(items) = app.try_state::<TrayItems>() {
+        if let Err(e) = items.tray.set_icon(Some(icon_for(state))) {
+            error!("[tray] Failed to set icon: {}", e);
+        }
+    }
+}
+
+/// Whether the live stats tooltip is enabled (read by the system monitor).
+pub fn stats_enabled() -> bool {
+    TRAY_STATS.load(Ordering::Relaxed)
+}
+
+/// Enable/disable the live stats tooltip. Disabling restores the plain tooltip.
+pub fn set_stats_enabled(app: &AppHandle, enabled: bool) {
+    TRAY_STATS.store(enabled, Ordering::SeqCst);
+    crate::settings::update(|s| s.tray_stats = enabled);
+    if enabled {
+        crate::system_monitor::ensure_running();
+    } else if let Some(items) = app.try_state::<TrayItems>() {
+        let _ = items.tray.set_tooltip(Some(DEFAULT_TOOLTIP));
+    }
+    info!(
+        "[tray] Stats tooltip {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+}
+
+/// Render "CPU 23% · RAM 41%" from the latest monitor sample.
+pub fn update_stats_tooltip(app: &AppHandle, data: &crate::system_monitor::SystemData) {
+    if !stats_enabled() {
+        return;
+    }
+    let Some(items) = app.try_state::<TrayItems>() else {
+        return;
+    };
+    let cpu = data.cpu.as_ref().map(|c| c.usage).unwrap_or(0.0);
+    let ram = data
+        .memory
+        .as_ref()
+        .filter(|m| m.total > 0)
+        .map(|m| m.used as f64 / m.total as f64 * 100.0)
+        .unwrap_or(0.0);
+    let text = format!("CPU {:.0}% · RAM {:.0}%", cpu, ram);
+    let _ = items.tray.set_tooltip(Some(text.as_str()));
+}
@@ -0,0 +1,239 @@
+//! Auto-updater — shared update check + background update watcher.
+//!
+//! The watcher only notifies (`update-available`); installing always goes
+//! through the explicit `download_and_install_update` command.
+
+use crate::commands_core::{self, SkippedVersion, UpdateChannel, UpdateInfo};
+use crate::error::{AppError, AppResult};
+use log::{info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+static WATCHER_RUNNING: AtomicBool = AtomicBool::new(false);
+static AUTO_CHECK_ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// How often a disabled watcher looks at the switch again.
+const DISABLED_POLL: Duration = Duration::from_secs(5);
+/// Delay before the first background check, so startup isn't slowed down.
+const WATCHER_INITIAL_DELAY: Duration = Duration::from_secs(60);
+/// First retry delay after a failed check; doubles up to the regular interval.
+const WATCHER_ERROR_BACKOFF: Duration = Duration::from_secs(5 * 60);
+/// Quick in-place retries of a check that hit a transient network error.
+const CHECK_RETRIES: u32 = 3;
+/// Delay before the first quick retry; doubles on each attempt.
+const CHECK_RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+
+pub fn build_updater(
+    app: &tauri::AppHandle,
+    endpoint: Option<String>,
+    channel: UpdateChannel,
+) -> AppResult<tauri_plugin_updater::Updater> {
+    use tauri_plugin_updater::UpdaterExt;
+    // An explicit endpoint wins; otherwise beta points at its own manifest
+    let endpoint =
+        endpoint.or_else(|| (channel == UpdateChannel::Beta).then(|| channel.manifest_url()));
+    if let Some(url) = endpoint {
+        commands_core::validate_updater_endpoint(&url)?;
+        let parsed: url::Url = url
+            .parse()
+            .map_err(|e| AppError::Updater(format!("Invalid URL: {}", e)))?;
+        app.updater_builder()
+            .endpoints(vec![parsed])
+            .map_err(|e| AppError::Updater(format!("Invalid endpoint: {}", e)))?
+            .build()
+            .map_err(|e| AppError::Updater(format!("Build failed: {}", e)))
+    } else {
+        app.updater()
+            .map_err(|e| AppError::Updater(format!("Updater not available: {}", e)))
+    }
+}
+
+/// Check for an update and validate it against downgrade rules.
+/// A version the user chose to skip is reported as no update. Every check,
+/// manual or background, ends with an `update-check-result` event.
+pub async fn check(
+    app: &tauri::AppHandle,
+    endpoint: Option<String>,
+    channel: UpdateChannel,
+) -> AppResult<Option<UpdateInfo>> {
+    use crate::events::{AppEvent, EmitAppEvent};
+
+    let mut attempt = 0;
+    let result = loop {
+        match check_once(app, endpoint.clone(), channel).await {
+            Err(e) if attempt < CHECK_RETRIES && commands_core::is_transient_update_error(&e) => {
+                let delay = commands_core::backoff_delay(CHECK_RETRY_BASE_DELAY, attempt);
+                attempt += 1;
+                warn!(
+                    "[updater] Check failed ({}), retry {}/{} in {}s",
+                    e,
+                    attempt,
+                    CHECK_RETRIES,
+                    delay.as_secs()
+                );
+                // Sleep off the async runtime's worker threads
+                let _ =
+                    tauri::async_runtime::spawn_blocking(move || std::thread::sleep(delay)).await;
+            }
+            result => break result,
+        }
+    };
+    let (status, version) = match &result {
+        Ok(Some(update)) => ("available", Some(update.version.clone())),
+        Ok(None) => ("up-to-date", None),
+        Err(_) => ("error", None),
+    };
+    let _ = app.emit_app_event(&AppEvent::UpdateCheckResult {
+        status: status.into(),
+        version,
+    });
+    result
+}
+
+async fn check_once(
+    app: &tauri::AppHandle,
+    endpoint: Option<String>,
+    channel: UpdateChannel,
+) -> AppResult<Option<UpdateInfo>> {
+    let updater = build_updater(app, endpoint, channel)?;
+
+    match updater.check().await {
+        Ok(Some(update)) => {
+            // Reject downgrades to prevent rollback attacks
+            commands_core::validate_update_version(env!("CARGO_PKG_VERSION"), &update.version)?;
+            let skipped = crate::settings::get().skipped_version;
+            match commands_core::check_skipped_version(&update.version, skipped.as_deref()) {
+                SkippedVersion::Skipped => {
+                    info!(
+                        "[updater] v{} available but skipped by user",
+                        update.version
+                    );
+                    return Ok(None);
+                }
+                SkippedVersion::Superseded => {
+                    info!("[updater] Newer than skipped version, clearing skip");
+                    crate::settings::update(|s| s.skipped_version = None);
+                }
+                SkippedVersion::NotSkipped => {}
+            }
+            info!("[updater] Update available: v{}", update.version);
+            Ok(Some(UpdateInfo {
+                version: update.version.clone(),
+                current_version: env!("CARGO_PKG_VERSION").to_string(),
+                body: update.body.clone(),
+                date: update.date.map(|d| d.to_string()),
+            }))
+        }
+        Ok(None) => Ok(None),
+        Err(e) => Err(AppError::Updater(format!(
+            "Update check failed: {}",
+            error_chain(&e)
+        ))),
+    }
+}
+
+/// `e` followed by its sources, so logs keep the root cause (DNS, TLS, ...).
+fn error_chain(e: &dyn std::error::Error) -> String {
+    let mut text = e.to_string();
+    let mut source = e.source();
+    while let Some(cause) = source {
+        text.push_str(": ");
+        text.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    text
+}
+
+/// Start the background update watcher thread.
+/// Checks every `interval_hours` and emits `update-available` — never installs.
+pub fn start_update_watcher(app: tauri::AppHandle, interval_hours: u64) {
+    if WATCHER_RUNNING.swap(true, Ordering::SeqCst) {
+        info!("[updater] Watcher already running");
+        return;
+    }
+    AUTO_CHECK_ENABLED.store(crate::settings::get().auto_update_check, Ordering::SeqCst);
+
+    info!(
+        "[updater] Starting background watcher ({}h interval)",
+        interval_hours
+    );
+
+    std::thread::spawn(move || {
+        use crate::events::{AppEvent, EmitAppEvent};
+
+        let interval = Duration::from_secs(interval_hours.max(1) * 3600);
+        let mut backoff = WATCHER_ERROR_BACKOFF;
+        std::thread::sleep(WATCHER_INITIAL_DELAY);
+
+        loop {
+            if !AUTO_CHECK_ENABLED.load(Ordering::SeqCst) {
+                std::thread::sleep(DISABLED_POLL);
+                continue;
+            }
+
+            let channel = configured_channel();
+            let next = match tauri::async_runtime::block_on(check(&app, None, channel)) {
+                Ok(Some(update)) => {
+                    let _ = app.emit_app_event(&AppEvent::UpdateAvailable(update));
+                    backoff = WATCHER_ERROR_BACKOFF;
+                    interval
+                }
+                Ok(None) => {
+                    backoff = WATCHER_ERROR_BACKOFF;
+                    interval
+                }
+                Err(e) => {
+                    warn!(
+                        "[updater] Background check failed, retrying in {}s: {}",
+                        backoff.as_secs(),
+                        e
+                    );
+                    let wait = backoff;
+                    backoff = (backoff * 2).min(interval);
+                    wait
+                }
+            };
+            std::thread::sleep(next);
+        }
+    });
+}
+
+/// Enable/disable background update checks (persisted).
+pub fn set_auto_update_check(enabled: bool) {
+    AUTO_CHECK_ENABLED.store(enabled, Ordering::SeqCst);
+    crate::settings::update(|s| s.auto_update_check = enabled);
+    info!(
+        "[updater] Background checks {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+}
+
+/// Channel from settings; an unknown stored name falls back to stable.
+pub fn configured_channel() -> UpdateChannel {
+    UpdateChannel::parse(crate::settings::get().update_channel.as_deref()).unwrap_or_default()
+}
+
+/// Persist the channel used by background checks and by commands that don't
+/// name one.
+pub fn set_channel(channel: Option<String>) -> AppResult<()> {
+    UpdateChannel::parse(channel.as_deref())?;
+    info!(
+        "[updater] Channel: {}",
+        channel.as_deref().unwrap_or("stable")
+    );
+    crate::settings::update(|s| s.update_channel = channel);
+    Ok(())
+}
+
+/// Stop notifying about `version` until something newer is released.
+pub fn skip_version(version: String) -> AppResult<()> {
+    // Reject garbage so the skip can always be compared later
+    commands_core::compare_semver(&version, &version)?;
+    info!("[updater] Skipping v{}", version);
+    crate::settings::update(|s| s.skipped_version = Some(version));
+    Ok(())
+}
+
+pub fn clear_skipped_version() {
+    crate::settings::update(|s| s.skipped_version = None);
+}
@@ -4,38 +4,302 @@
 use log::{error, info};
 #[cfg(target_os = "windows")]
 use std::sync::atomic::AtomicIsize;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering};
+use std::sync::Mutex;
+
+use crate::commands_core::LayerMode;
 
 static ICONS_RESTORED: AtomicBool = AtomicBool::new(false);
+/// Lowercased exe names that pause the wallpaper while in the foreground.
+static PAUSE_APPS: std::sync::LazyLock<Mutex<std::collections::HashSet<String>>> =
+    std::sync::LazyLock::new(|| Mutex::new(std::collections::HashSet::new()));
+/// A listed app or a fullscreen window currently has the foreground.
+static FOREGROUND_PAUSED: AtomicBool = AtomicBool::new(false);
+static LAYER_INJECTED: AtomicBool = AtomicBool::new(false);
+static LAYER_WORKER_W_FOUND: AtomicBool = AtomicBool::new(false);
+static LAYER_MONITOR_COUNT: AtomicU32 = AtomicU32::new(0);
+static LAYER_LAST_ERROR: Mutex<Option<String>> = Mutex::new(None);
 #[cfg(target_os = "windows")]
 static HOOK_HANDLE_GLOBAL: AtomicIsize = AtomicIsize::new(0);
 #[cfg(target_os = "windows")]
 static IS_SESSION_ACTIVE: AtomicBool = AtomicBool::new(true);
 #[cfg(target_os = "windows")]
 static WATCHDOG_PARENT: AtomicIsize = AtomicIsize::new(0);
+/// Zombie-window watchdog period in seconds; 0 pauses it.
+static WATCHDOG_INTERVAL_SECS: AtomicU64 = AtomicU64::new(DEFAULT_WATCHDOG_INTERVAL_SECS);
+#[cfg(target_os = "windows")]
+static WATCHDOG_RUNNING: AtomicBool = AtomicBool::new(false);
+#[cfg(target_os = "windows")]
+static EXPLORER_WATCHER_RUNNING: AtomicBool = AtomicBool::new(false);
+/// Bumped per setup so only the newest `wait_for_controller` stays live and
+/// publishes the controller; a superseded waiter exits on its next poll.
+#[cfg(target_os = "windows")]
+static CONTROLLER_WAIT_GEN: AtomicU64 = AtomicU64::new(0);
+/// Serializes `reinit_window_layer` so overlapping repairs can't interleave.
+#[cfg(target_os = "windows")]
+static REINIT_LOCK: Mutex<()> = Mutex::new(());
+
+pub const DEFAULT_WATCHDOG_INTERVAL_SECS: u64 = 30;
+const MIN_WATCHDOG_INTERVAL_SECS: u64 = 5;
+/// For emitting events from Win32 callbacks that have no Tauri context.
+#[cfg(target_os = "windows")]
+static APP_HANDLE: std::sync::OnceLock<tauri::AppHandle> = std::sync::OnceLock::new();
+/// Monitor index the wallpaper is bound to; `u32::MAX` spans all monitors.
+static WALLPAPER_MONITOR: AtomicU32 = AtomicU32::new(u32::MAX);
+/// Lay the wallpaper out on monitor work areas, leaving the taskbar uncovered.
+static RESPECT_TASKBAR: AtomicBool = AtomicBool::new(false);
+/// Class background brush for our window; 0 means the stock black brush.
+#[cfg(target_os = "windows")]
+static BACKGROUND_BRUSH: AtomicIsize = AtomicIsize::new(0);
+/// Virtual desktop bounds from the last detection or display change.
+static VIRTUAL_BOUNDS: Mutex<Option<crate::commands_core::ScreenRect>> = Mutex::new(None);
+/// Last AC line state seen on WM_POWERBROADCAST: 0 battery, 1 AC, 255 unknown.
+#[cfg(target_os = "windows")]
+static LAST_AC_LINE: AtomicU8 = AtomicU8::new(255);
+/// Coalesces bursts of WM_DISPLAYCHANGE into a single re-detection.
+#[cfg(target_os = "windows")]
+static DISPLAY_CHANGE_PENDING: AtomicBool = AtomicBool::new(false);
+/// Last `setup-progress` phase, for a frontend that loads after setup began.
+static SETUP_PHASE: Mutex<&str> = Mutex::new(if cfg!(target_os = "windows") {
+    "detecting"
+} else {
+    "ready"
+});
 
 // ==============================================================================
 // Public API
 // ==============================================================================
 
+/// Desktop layer attachment state, updated on every (re)injection attempt.
+#[typeshare::typeshare]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerStatus {
+    pub injected: bool,
+    pub worker_w_found: bool,
+    pub monitor_count: u32,
+    pub last_error: Option<String>,
+}
+
+/// Health check of the pieces the wallpaper depends on, built from state the
+/// layer already tracks. Security software commonly blocks the hook or WorkerW.
+#[typeshare::typeshare]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentDiagnosis {
+    pub progman_found: bool,
+    pub worker_w_found: bool,
+    pub mouse_hook_installed: bool,
+    pub composition_controller_found: bool,
+    pub session_active: bool,
+    /// One line per failed check, or an all-clear message.
+    pub summary: String,
+}
+
+/// Installed WebView2 runtime and whether our composition controller is live.
+#[typeshare::typeshare]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebViewInfo {
+    /// e.g. "126.0.2592.68"; `None` when no runtime is installed or it can't be queried
+    pub runtime_version: Option<String>,
+    pub controller_attached: bool,
+}
+
+/// Time spent in the low-level mouse hook over the last completed window of
+/// at least one second, while hook timing is on.
+#[typeshare::typeshare]
+#[derive(Debug, Clone, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookStats {
+    pub enabled: bool,
+    /// Hook calls in the window
+    pub calls: u32,
+    pub avg_micros: f64,
+    pub max_micros: f64,
+}
+
+/// Physical monitor rectangle in virtual-screen coordinates.
+#[typeshare::typeshare]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorRect {
+    pub left: i32,
+    pub top: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// A monitor as listed by `get_monitors`, in enumeration order.
+#[typeshare::typeshare]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    pub index: u32,
+    /// Device name, e.g. `\\.\DISPLAY2`; can change across reboots and reconnects
+    pub name: String,
+    /// Device interface path of the monitor, stable for a given monitor and
+    /// port; the device name when Windows doesn't report one
+    pub id: String,
+    pub left: i32,
+    pub top: i32,
+    pub width: i32,
+    pub height: i32,
+    pub is_primary: bool,
+}
+
+/// Effective DPI scale of a monitor, indexed like `get_monitors`.
+#[typeshare::typeshare]
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorDpi {
+    pub index: u32,
+    /// 1.0 at 96 DPI, 1.5 at 144 DPI, ...
+    pub scale_factor: f64,
+}
+
+/// Latest desktop setup phase, as also emitted in `setup-progress`: "detecting",
+/// "injecting", "waiting-controller", "hook-started", then "ready" or "failed".
+/// "controller-timeout" means WebView2 is slow; "ready" can still follow.
+#[tauri::command]
+pub fn get_setup_phase() -> String {
+    SETUP_PHASE.lock().unwrap().to_string()
+}
+
 pub fn setup_desktop_window(_window: &tauri::WebviewWindow) {
     #[cfg(target_os = "windows")]
     {
+        use tauri::Manager;
+        let _ = APP_HANDLE.set(_window.app_handle().clone());
         info!("[window_layer] Starting desktop window setup phase...");
         if let Err(e) = ensure_in_worker_w(_window) {
             error!(
                 "[window_layer] CRITICAL: Failed to setup desktop layer: {}",
                 e
             );
+            record_injection_failure(&e);
+            emit_setup_phase("failed");
         } else {
             info!("[window_layer] Desktop layer setup completed successfully.");
         }
+        start_foreground_watcher();
     }
 }
 
+/// Replace the list of apps that pause the wallpaper and persist it.
+#[tauri::command]
+pub fn set_pause_apps(exe_names: Vec<String>) -> crate::error::AppResult<()> {
+    let names = normalize_pause_apps(&exe_names)?;
+    crate::settings::update(|s| s.pause_apps = names.clone());
+    log::info!("[window_layer] {} pause app(s) configured", names.len());
+    *PAUSE_APPS.lock().unwrap() = names.into_iter().collect();
+    Ok(())
+}
+
+/// Restore the persisted pause list.
+pub fn load_pause_apps(exe_names: &[String]) {
+    let names = normalize_pause_apps(exe_names).unwrap_or_default();
+    *PAUSE_APPS.lock().unwrap() = names.into_iter().collect();
+}
+
+/// Bare file names only ("Resolve.exe"), compared case-insensitively.
+fn normalize_pause_apps(exe_names: &[String]) -> crate::error::AppResult<Vec<String>> {
+    exe_names
+        .iter()
+        .map(|n| n.trim().to_lowercase())
+        .filter(|n| !n.is_empty())
+        .map(|n| {
+            if n.contains(['/', '\\', ':']) || n.len() > 260 {
+                Err(crate::error::AppError::Validation(format!(
+                    "Invalid executable name: {}",
+                    n
+                )))
+            } else {
+                Ok(n)
+            }
+        })
+        .collect()
+}
+
+/// Whether a listed app or a fullscreen window is in the foreground.
+pub fn foreground_paused() -> bool {
+    FOREGROUND_PAUSED.load(Ordering::SeqCst)
+}
+
+#[tauri::command]
+pub fn set_desktop_icons_visible(
+    app: tauri::AppHandle,
+    visible: bool,
+) -> crate::error::AppResult<()> {
+    apply_desktop_icons_visible(visible);
+    crate::settings::update(|s| s.show_desktop_icons = visible);
+    crate::tray::sync_desktop_icons_checked(&app, visible);
+    Ok(())
+}
+
+/// Set the color shown behind the wallpaper (e.g. while it loads), persisted.
 #[tauri::command]
+pub fn set_background_color(window: tauri::WebviewWindow, r: u8, g: u8, b: u8) {
+    apply_background_color(&window, [r, g, b]);
+    crate::settings::update(|s| s.background_color = [r, g, b]);
+}
+
+/// Apply a background color to the WebView and the native window class.
+pub fn apply_background_color(window: &tauri::WebviewWindow, [r, g, b]: [u8; 3]) {
+    let _ = window.set_background_color(Some(tauri::webview::Color(r, g, b, 255)));
+    #[cfg(target_os = "windows")]
+    unsafe {
+        use windows::Win32::Foundation::{BOOL, COLORREF, HWND, LPARAM};
+        use windows::Win32::Graphics::Gdi::{
+            CreateSolidBrush, DeleteObject, RedrawWindow, HBRUSH, RDW_ALLCHILDREN, RDW_ERASE,
+            RDW_INVALIDATE,
+        };
+        use windows::Win32::UI::WindowsAndMessaging::{
+            EnumChildWindows, SetClassLongPtrW, GCLP_HBRBACKGROUND,
+        };
+
+        unsafe extern "system" fn set_child_brush(child: HWND, brush: LPARAM) -> BOOL {
+            SetClassLongPtrW(child, GCLP_HBRBACKGROUND, brush.0);
+            BOOL(1)
+        }
+
+        // Without a window there is nothing to paint, and creating the brush
+        // first would leak it
+        let Ok(h) = window.hwnd() else {
+            return;
+        };
+        let hwnd = HWND(h.0 as *mut _);
+        let brush = CreateSolidBrush(COLORREF(r as u32 | ((g as u32) << 8) | ((b as u32) << 16)));
+        let old = BACKGROUND_BRUSH.swap(brush.0 as isize, Ordering::SeqCst);
+        SetClassLongPtrW(hwnd, GCLP_HBRBACKGROUND, brush.0 as isize);
+        // The WebView children got the old brush in `resize_webview`
+        let _ = EnumChildWindows(hwnd, Some(set_child_brush), LPARAM(brush.0 as isize));
+        let _ = RedrawWindow(
+            hwnd,
+            None,
+            None,
+            RDW_ERASE | RDW_INVALIDATE | RDW_ALLCHILDREN,
+        );
+        // Only free the previous brush once no class references it
+        if old != 0 {
+            let _ = DeleteObject(HBRUSH(old as *mut _));
+        }
+    }
+}
+
+/// Brush for `GCLP_HBRBACKGROUND`: the user's color, or stock black.
+#[cfg(target_os = "windows")]
+fn background_brush() -> isize {
+    use windows::Win32::Graphics::Gdi::{GetStockObject, BLACK_BRUSH};
+    match BACKGROUND_BRUSH.load(Ordering::SeqCst) {
+        0 => unsafe { GetStockObject(BLACK_BRUSH).0 as isize },
+        brush => brush,
+    }
+}
+
+/// Show/hide SysListView32 without touching the persisted preference.
 #[allow(unused_variables)]
-pub fn set_desktop_icons_visible(visible: bool) -> crate::error::AppResult<()> {
+pub fn apply_desktop_icons_visible(visible: bool) {
     #[cfg(target_os = "windows")]
     {
         use windows::Win32::Foundation::HWND;
@@ -48,183 +312,1403 @@ pub fn set_desktop_icons_visible(visible: bool) -> crate::error::AppResult<()> {
             }
         }
     }
-    Ok(())
 }
 
-pub fn restore_desktop_icons_and_unhook() {
-    if !ICONS_RESTORED.swap(true, Ordering::SeqCst) {
-        #[cfg(target_os = "windows")]
-        {
-            use windows::Win32::Foundation::HWND;
-            use windows::Win32::UI::WindowsAndMessaging::{
-                ShowWindow, UnhookWindowsHookEx, HHOOK, SW_SHOW,
-            };
-
-            let slv = mouse_hook::get_syslistview_hwnd();
-            if slv != 0 {
-                unsafe {
-                    // ShowWindow returns BOOL (previous visibility state), not Result
-                    let _ = ShowWindow(HWND(slv as *mut _), SW_SHOW);
-                }
-            }
+/// Set the zombie-window watchdog period (`None` pauses it). Applies from the
+/// watchdog's next tick, no restart needed.
+#[tauri::command]
+pub fn set_watchdog_interval(secs: Option<u64>) {
+    let secs = apply_watchdog_interval(secs);
+    crate::settings::update(|s| s.watchdog_interval_secs = secs);
+}
 
-            let hook_ptr = HOOK_HANDLE_GLOBAL.load(Ordering::SeqCst);
-            if hook_ptr != 0 {
-                unsafe {
-                    if let Err(e) = UnhookWindowsHookEx(HHOOK(hook_ptr as *mut _)) {
-                        error!("[window_layer] Unhook mouse hook failed: {:?}", e);
-                    }
-                }
-            }
-        }
+/// Load the persisted watchdog period (before injection starts the watchdog).
+/// Returns the period actually applied, raised to the minimum.
+pub fn apply_watchdog_interval(secs: Option<u64>) -> Option<u64> {
+    let secs = secs.map(|s| s.max(MIN_WATCHDOG_INTERVAL_SECS));
+    match secs {
+        Some(s) => log::info!("[watchdog] Interval {}s", s),
+        None => log::info!("[watchdog] Paused"),
     }
+    WATCHDOG_INTERVAL_SECS.store(secs.unwrap_or(0), Ordering::SeqCst);
+    secs
 }
 
-// ==============================================================================
-// Windows: Helper Functions
-// ==============================================================================
+/// Cap forwarded mouse moves at `hz` per second (0 forwards every move).
+/// Buttons and wheel are never throttled; a deferred move is flushed ahead
+/// of them so ordering is preserved.
+#[tauri::command]
+pub fn set_mouse_forward_rate(hz: u32) {
+    apply_mouse_forward_rate(hz);
+    crate::settings::update(|s| s.mouse_forward_hz = hz);
+}
 
-/// Zero-allocation UTF-16 class name comparison.
-/// CRITICAL for mouse hook performance — avoids heap allocations on the
-/// global Windows input thread where String::from_utf16_lossy would cause
-/// system-wide micro-stutters.
-#[cfg(target_os = "windows")]
-unsafe fn is_class_name(hwnd: windows::Win32::Foundation::HWND, expected: &str) -> bool {
-    use windows::Win32::UI::WindowsAndMessaging::GetClassNameW;
-    let mut buf = [0u16; 128];
-    let len = GetClassNameW(hwnd, &mut buf) as usize;
-    if len != expected.len() {
-        return false;
+/// Load the persisted mouse-move rate limit.
+pub fn apply_mouse_forward_rate(hz: u32) {
+    #[cfg(target_os = "windows")]
+    mouse_hook::set_move_interval_ms(if hz == 0 { 0 } else { (1000 / hz).max(1) });
+    if hz == 0 {
+        log::info!("[hook] Mouse moves forwarded unthrottled");
+    } else {
+        log::info!("[hook] Mouse moves capped at {}Hz", hz);
     }
-    expected
-        .encode_utf16()
-        .zip(buf[..len].iter())
-        .all(|(a, b)| a == *b)
 }
 
-// ==============================================================================
-// Windows: Desktop Detection
-// ==============================================================================
+/// Turn the hook DLL's WM_MOUSELEAVE suppression on or off without unloading
+/// it, for widgets that end up stuck in `:hover`. Persisted.
+#[tauri::command]
+pub fn set_mouseleave_suppression(enabled: bool) {
+    apply_mouseleave_suppression(enabled);
+    crate::settings::update(|s| s.mouseleave_suppression = enabled);
+}
 
-#[cfg(target_os = "windows")]
-struct DesktopDetection {
-    progman: windows::Win32::Foundation::HWND,
-    explorer_pid: u32,
-    target_parent: windows::Win32::Foundation::HWND,
-    syslistview: windows::Win32::Foundation::HWND,
-    /// Sibling window that must stay IN FRONT of target_parent (Z-order).
-    /// Win11 24H2+: SHELLDLL_DefView (child of Progman).
-    /// Legacy: WorkerW that contains SHELLDLL_DefView.
-    zorder_anchor: windows::Win32::Foundation::HWND,
-    v_width: i32,
-    v_height: i32,
+/// Load the persisted leave-suppression switch.
+pub fn apply_mouseleave_suppression(enabled: bool) {
+    #[cfg(target_os = "windows")]
+    mouse_hook::set_leave_suppression(enabled);
+    log::info!(
+        "[hook] WM_MOUSELEAVE suppression {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
 }
 
-#[cfg(target_os = "windows")]
-fn detect_desktop() -> Result<DesktopDetection, crate::error::AppError> {
+/// Let the hook hand clicks to the desktop icons (SysListView32). Off, the
+/// wallpaper owns every click and the icons stop reacting, for icon-less
+/// setups. Persisted.
+#[tauri::command]
+pub fn set_icon_interaction(enabled: bool) {
+    apply_icon_interaction(enabled);
+    crate::settings::update(|s| s.icon_interaction = enabled);
+}
+
+/// Load the persisted icon interaction switch.
+pub fn apply_icon_interaction(enabled: bool) {
+    #[cfg(target_os = "windows")]
+    mouse_hook::set_icon_interaction(enabled);
+    log::info!(
+        "[hook] Desktop icon interaction {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+}
+
+/// Time every `hook_proc` call with `QueryPerformanceCounter`, for tracking
+/// down input stutter. Off by default and not persisted; while off the hook
+/// pays a single atomic load.
+#[tauri::command]
+pub fn set_hook_stats_enabled(enabled: bool) {
+    #[cfg(target_os = "windows")]
+    mouse_hook::set_timing_enabled(enabled);
+    log::info!(
+        "[hook] Timing {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+}
+
+/// Hook call count, mean and worst time of the last completed window.
+#[tauri::command]
+pub fn get_hook_stats() -> HookStats {
+    #[cfg(target_os = "windows")]
+    {
+        mouse_hook::timing_stats()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        HookStats::default()
+    }
+}
+
+/// Set by `hard_reload_webview`, consumed when the new page finishes loading.
+static HARD_RELOAD_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Navigate the WebView back to the app root without touching the window, so
+/// it stays injected. The init script runs again via `on_page_load`, and
+/// `webview-reloaded` is emitted once the page has finished loading.
+#[tauri::command]
+pub fn hard_reload_webview(app: tauri::AppHandle) -> crate::error::AppResult<()> {
     use crate::error::AppError;
-    use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT, WPARAM};
-    use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, HDC, HMONITOR};
-    use windows::Win32::UI::WindowsAndMessaging::*;
+    use tauri::Manager;
 
-    unsafe {
-        let progman = FindWindowW(windows::core::w!("Progman"), None)
-            .map_err(|_| AppError::WindowLayer("Could not find Progman".into()))?;
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| AppError::WindowNotFound("main".into()))?;
+    let mut url = window.url()?;
+    url.set_path("/");
+    url.set_query(None);
+    url.set_fragment(None);
+    HARD_RELOAD_PENDING.store(true, Ordering::SeqCst);
+    log::info!("[window_layer] Hard reload → {}", url);
+    window.navigate(url)?;
+    Ok(())
+}
 
-        let mut explorer_pid: u32 = 0;
-        GetWindowThreadProcessId(progman, Some(&mut explorer_pid));
+/// Called from `on_page_load` (Finished). Completes a pending hard reload.
+pub fn on_page_finished(app: &tauri::AppHandle) {
+    use crate::events::{AppEvent, EmitAppEvent};
 
-        // Force Windows to spawn the wallpaper WorkerW layer.
-        // This is an undocumented Progman message discovered via reverse engineering;
-        // it triggers creation of the WorkerW window behind the desktop icons.
-        const PROGMAN_SPAWN_WORKERW: u32 = 0x052C;
-        let mut msg_result: usize = 0;
-        let _ = SendMessageTimeoutW(
-            progman,
-            PROGMAN_SPAWN_WORKERW,
-            WPARAM(0x0D),
-            LPARAM(1),
-            SMTO_NORMAL,
-            1000,
-            Some(&mut msg_result),
-        );
-        std::thread::sleep(std::time::Duration::from_millis(150));
+    if !HARD_RELOAD_PENDING.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    #[cfg(target_os = "windows")]
+    mouse_hook::refresh_webview_handles();
+    let _ = app.emit_app_event(&AppEvent::WebViewReloaded);
+}
 
-        let mut target_parent;
-        let mut shell_for_slv;
+/// Current layer mode, as an index into `LayerMode::ALL`.
+static LAYER_MODE: AtomicU8 = AtomicU8::new(0);
 
-        // 1. Detection Win11 24H2+: SHELLDLL_DefView is direct child of Progman
-        let shell_view = FindWindowExW(
-            progman,
-            HWND::default(),
-            windows::core::w!("SHELLDLL_DefView"),
-            None,
-        )
-        .unwrap_or_default();
+fn current_layer_mode() -> LayerMode {
+    LayerMode::ALL[LAYER_MODE.load(Ordering::SeqCst) as usize]
+}
 
-        if !shell_view.is_invalid() {
-            target_parent =
-                FindWindowExW(progman, HWND::default(), windows::core::w!("WorkerW"), None)
-                    .unwrap_or_default();
-            shell_for_slv = shell_view;
-        } else {
-            // 2. Fallback Win10/Win11
-            struct SearchData {
-                parent: HWND,
-                sv: HWND,
-            }
-            let mut data = SearchData {
-                parent: HWND::default(),
-                sv: HWND::default(),
-            };
+#[tauri::command]
+pub fn get_window_layer() -> String {
+    current_layer_mode().name().into()
+}
 
-            unsafe extern "system" fn enum_cb(hwnd: HWND, lp: LPARAM) -> BOOL {
-                if lp.0 == 0 {
-                    return BOOL(0);
-                }
-                let sv = FindWindowExW(
-                    hwnd,
-                    HWND::default(),
-                    windows::core::w!("SHELLDLL_DefView"),
-                    None,
-                )
-                .unwrap_or_default();
-                if !sv.is_invalid() {
-                    let d = &mut *(lp.0 as *mut SearchData);
-                    d.sv = sv;
-                    d.parent =
-                        FindWindowExW(HWND::default(), hwnd, windows::core::w!("WorkerW"), None)
-                            .unwrap_or_default();
-                    return BOOL(0);
-                }
-                BOOL(1)
-            }
-            let _ = EnumWindows(Some(enum_cb), LPARAM(&mut data as *mut _ as isize));
-            target_parent = data.parent;
-            shell_for_slv = data.sv;
-        }
+/// Switch between desktop injection and a detached normal/topmost/overlay window.
+#[tauri::command]
+pub fn set_window_layer(mode: String) -> crate::error::AppResult<()> {
+    let mode = LayerMode::parse(&mode)?;
+    apply_layer_mode(mode)?;
+    crate::settings::update(|s| s.layer_mode = mode.name().into());
+    Ok(())
+}
 
-        // Compute Z-order anchor: the sibling that must stay IN FRONT
-        // of target_parent so WindowFromPoint returns SysListView32.
-        let zorder_anchor = if !shell_view.is_invalid() {
-            // Win11 24H2+: SHELLDLL_DefView is a direct sibling of WorkerW
-            shell_view
-        } else if !shell_for_slv.is_invalid() {
-            // Legacy: SHELLDLL_DefView is inside WorkerW A; get WorkerW A
-            GetParent(shell_for_slv).unwrap_or_default()
-        } else {
-            HWND::default()
-        };
+/// Flip between the desktop layer and a normal window; returns the new mode.
+#[tauri::command]
+pub fn toggle_window_layer() -> crate::error::AppResult<String> {
+    let next = match current_layer_mode() {
+        LayerMode::Desktop => LayerMode::Normal,
+        LayerMode::Normal | LayerMode::Topmost | LayerMode::Overlay => LayerMode::Desktop,
+    };
+    set_window_layer(next.name().into())?;
+    Ok(next.name().into())
+}
 
-        if target_parent.is_invalid() {
-            target_parent = progman;
-        }
+/// True while `preview_wallpaper` has the window raised above everything.
+static PREVIEW_ACTIVE: AtomicBool = AtomicBool::new(false);
 
-        let mut syslistview = HWND::default();
-        unsafe extern "system" fn find_slv(hwnd: HWND, lp: LPARAM) -> BOOL {
-            if lp.0 == 0 {
-                return BOOL(0);
-            }
+/// Raise the wallpaper as a topmost window over the whole render area so it
+/// can be previewed uncovered; `false` puts it back into the current layer
+/// mode. The layer mode itself is untouched and watchdog re-injection is held
+/// off meanwhile. The hook stops forwarding during the preview.
+#[tauri::command]
+pub fn preview_wallpaper(enabled: bool) -> crate::error::AppResult<()> {
+    if PREVIEW_ACTIVE.swap(enabled, Ordering::SeqCst) == enabled {
+        return Ok(());
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let our = mouse_hook::get_webview_hwnd();
+        if our == 0 {
+            PREVIEW_ACTIVE.store(false, Ordering::SeqCst);
+            return Err(crate::error::AppError::WindowNotFound(
+                "wallpaper webview".into(),
+            ));
+        }
+        mouse_hook::set_forwarding_paused(enabled);
+        if enabled {
+            mouse_hook::set_desktop_mode(false);
+            detach_from_desktop(our, true);
+        } else {
+            // Same path as a mode switch: re-detects and re-injects for desktop
+            apply_layer_mode(current_layer_mode())?;
+        }
+        log::info!(
+            "[window_layer] Preview {}",
+            if enabled { "started" } else { "ended" }
+        );
+        Ok(())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        PREVIEW_ACTIVE.store(false, Ordering::SeqCst);
+        Err(crate::error::AppError::WindowLayer(
+            "Preview is only supported on Windows".into(),
+        ))
+    }
+}
+
+/// Re-apply the mode saved last session. An unknown value falls back to desktop.
+pub fn restore_layer_mode() {
+    let saved = crate::settings::get().layer_mode;
+    let mode = LayerMode::parse(&saved).unwrap_or_else(|e| {
+        log::warn!("[window_layer] Ignoring saved layer mode: {}", e);
+        LayerMode::default()
+    });
+    if mode != LayerMode::Desktop {
+        if let Err(e) = apply_layer_mode(mode) {
+            log::error!("[window_layer] Failed to restore layer mode: {}", e);
+        }
+    }
+}
+
+pub fn apply_layer_mode(mode: LayerMode) -> crate::error::AppResult<()> {
+    let index = LayerMode::ALL.iter().position(|m| *m == mode).unwrap_or(0);
+    #[cfg(target_os = "windows")]
+    let previous = current_layer_mode();
+    LAYER_MODE.store(index as u8, Ordering::SeqCst);
+    #[cfg(target_os = "windows")]
+    {
+        mouse_hook::set_desktop_mode(mode == LayerMode::Desktop);
+        let our = mouse_hook::get_webview_hwnd();
+        // Before setup there is no window yet; the mode is only recorded
+        if our != 0 {
+            if previous == LayerMode::Overlay && mode != LayerMode::Overlay {
+                set_overlay_styles(our, false);
+            }
+            match mode {
+                LayerMode::Desktop => reinject(our)?,
+                LayerMode::Normal => detach_from_desktop(our, false),
+                LayerMode::Topmost => detach_from_desktop(our, true),
+                LayerMode::Overlay => {
+                    detach_from_desktop(our, true);
+                    set_overlay_styles(our, true);
+                }
+            }
+        }
+    }
+    log::info!("[window_layer] Layer mode: {}", mode.name());
+    Ok(())
+}
+
+/// Layer modes the backend understands, for the settings dropdown.
+#[tauri::command]
+pub fn get_available_layer_modes() -> Vec<crate::commands_core::LayerModeInfo> {
+    crate::commands_core::LayerMode::available()
+}
+
+#[tauri::command]
+pub fn get_monitors() -> Vec<MonitorInfo> {
+    #[cfg(target_os = "windows")]
+    {
+        enumerate_monitor_info()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Vec::new()
+    }
+}
+
+/// Bounding box of all monitors in screen coordinates, the same bounds the
+/// desktop injection uses. The origin can be negative (monitors left of or
+/// above the primary). Refreshed on display change.
+#[tauri::command]
+pub fn get_virtual_desktop_bounds() -> crate::commands_core::ScreenRect {
+    if let Some(bounds) = *VIRTUAL_BOUNDS.lock().unwrap() {
+        return bounds;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        refresh_virtual_bounds(&enumerate_monitors())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        crate::commands_core::ScreenRect {
+            left: 0,
+            top: 0,
+            width: 0,
+            height: 0,
+        }
+    }
+}
+
+/// Per-monitor DPI scale. Monitors can differ, so the frontend should use the
+/// scale of the monitor a widget sits on. Changes emit `monitor-dpi-changed`.
+#[tauri::command]
+pub fn get_monitor_dpi() -> Vec<MonitorDpi> {
+    #[cfg(target_os = "windows")]
+    {
+        enumerate_monitor_dpi()
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Vec::new()
+    }
+}
+
+/// Bind the wallpaper to one monitor (`None` spans all of them), persisted.
+#[tauri::command]
+pub fn set_wallpaper_monitor(
+    app: tauri::AppHandle,
+    index: Option<usize>,
+) -> crate::error::AppResult<()> {
+    #[cfg(target_os = "windows")]
+    {
+        let count = enumerate_monitors().len();
+        if let Some(i) = index.filter(|&i| i >= count) {
+            return Err(crate::error::AppError::Validation(format!(
+                "Monitor index {} out of range ({} monitors)",
+                i, count
+            )));
+        }
+    }
+    apply_wallpaper_monitor(index);
+    crate::settings::update(|s| s.wallpaper_monitor = index);
+    crate::tray::rebuild_monitor_menu(&app);
+    #[cfg(target_os = "windows")]
+    relayout()?;
+    Ok(())
+}
+
+/// Size the wallpaper to the monitor work areas so the taskbar stays visibly
+/// on top, or (`false`) to the full monitors. Persisted. Input over the
+/// taskbar is never forwarded either way; with several monitors the window
+/// spans the bounding box of their work areas, clipped to the areas themselves.
+#[tauri::command]
+pub fn set_respect_taskbar(enabled: bool) -> crate::error::AppResult<()> {
+    apply_respect_taskbar(enabled);
+    crate::settings::update(|s| s.respect_taskbar = enabled);
+    #[cfg(target_os = "windows")]
+    relayout()?;
+    Ok(())
+}
+
+/// Record the taskbar preference before injection sizes the window.
+pub fn apply_respect_taskbar(enabled: bool) {
+    RESPECT_TASKBAR.store(enabled, Ordering::SeqCst);
+    log::info!(
+        "[window_layer] Wallpaper {} the taskbar",
+        if enabled { "avoids" } else { "spans under" }
+    );
+}
+
+/// Re-fit the window (and the WebView inside it) after the render bounds changed.
+#[cfg(target_os = "windows")]
+fn relayout() -> crate::error::AppResult<()> {
+    let our = mouse_hook::get_webview_hwnd();
+    if our == 0 {
+        return Ok(());
+    }
+    match current_layer_mode() {
+        LayerMode::Desktop => reinject(our)?,
+        // Detached modes re-apply themselves over the new bounds
+        mode => apply_layer_mode(mode)?,
+    }
+    let b = MonitorBounds::render(&enumerate_monitors());
+    resize_webview(
+        windows::Win32::Foundation::HWND(our as *mut _),
+        b.right - b.left,
+        b.bottom - b.top,
+    );
+    Ok(())
+}
+
+/// Wallpaper assigned to each connected monitor, indexed like `get_monitors`.
+/// Call again on `monitors-changed` to load the right content per monitor.
+#[tauri::command]
+pub fn get_monitor_assignments() -> Vec<crate::commands_core::MonitorAssignment> {
+    crate::commands_core::monitor_assignments(
+        &monitor_ids(),
+        &crate::settings::get().monitor_wallpapers,
+    )
+}
+
+/// Assign a wallpaper to one monitor, or clear its assignment with `None`.
+/// Persisted under the monitor's stable id so it follows the monitor across
+/// reboots and reconnects.
+#[tauri::command]
+pub fn set_monitor_wallpaper(
+    monitor_index: usize,
+    wallpaper_id: Option<String>,
+) -> crate::error::AppResult<()> {
+    if let Some(id) = wallpaper_id
+        .as_ref()
+        .filter(|id| !crate::commands_core::is_valid_wallpaper_id(id))
+    {
+        return Err(crate::error::AppError::Validation(format!(
+            "Invalid wallpaper id: {}",
+            id
+        )));
+    }
+    let ids = monitor_ids();
+    let (id, name) = ids.get(monitor_index).cloned().ok_or_else(|| {
+        crate::error::AppError::Validation(format!(
+            "Monitor index {} out of range ({} monitors)",
+            monitor_index,
+            ids.len()
+        ))
+    })?;
+    match &wallpaper_id {
+        Some(wallpaper) => log::info!(
+            "[window_layer] Monitor {} ({}) assigned wallpaper {}",
+            monitor_index,
+            name,
+            wallpaper
+        ),
+        None => log::info!(
+            "[window_layer] Monitor {} ({}) assignment cleared",
+            monitor_index,
+            name
+        ),
+    }
+    crate::settings::update(|s| match wallpaper_id {
+        Some(wallpaper) => {
+            s.monitor_wallpapers.insert(id, wallpaper);
+        }
+        None => {
+            s.monitor_wallpapers.remove(&id);
+        }
+    });
+    Ok(())
+}
+
+/// (stable id, device name) of each connected monitor, in enumeration order.
+fn monitor_ids() -> Vec<(String, String)> {
+    get_monitors().into_iter().map(|m| (m.id, m.name)).collect()
+}
+
+/// Screen areas where the hook never forwards input, so real windows layered
+/// over the wallpaper (sidebar gadgets) aren't clicked through. Replaces the
+/// previous set; an empty list removes all exclusions. Not persisted.
+#[tauri::command]
+pub fn set_hook_exclusion_rects(
+    rects: Vec<crate::commands_core::ScreenRect>,
+) -> crate::error::AppResult<()> {
+    crate::commands_core::validate_exclusion_rects(&rects)?;
+    #[cfg(target_os = "windows")]
+    mouse_hook::set_exclusion_rects(&rects);
+    log::info!("[window_layer] {} hook exclusion rect(s) set", rects.len());
+    Ok(())
+}
+
+/// The monitor the wallpaper is bound to, if any.
+pub fn wallpaper_monitor() -> Option<usize> {
+    match WALLPAPER_MONITOR.load(Ordering::SeqCst) {
+        u32::MAX => None,
+        i => Some(i as usize),
+    }
+}
+
+/// Record the monitor choice before injection sizes the window.
+pub fn apply_wallpaper_monitor(index: Option<usize>) {
+    let raw = index.map_or(u32::MAX, |i| i as u32);
+    WALLPAPER_MONITOR.store(raw, Ordering::SeqCst);
+    match index {
+        Some(i) => log::info!("[window_layer] Wallpaper bound to monitor {}", i),
+        None => log::info!("[window_layer] Wallpaper spans all monitors"),
+    }
+}
+
+#[tauri::command]
+pub fn get_layer_status() -> LayerStatus {
+    LayerStatus {
+        injected: LAYER_INJECTED.load(Ordering::SeqCst),
+        worker_w_found: LAYER_WORKER_W_FOUND.load(Ordering::SeqCst),
+        monitor_count: LAYER_MONITOR_COUNT.load(Ordering::SeqCst),
+        last_error: LAYER_LAST_ERROR.lock().unwrap().clone(),
+    }
+}
+
+#[tauri::command]
+pub fn diagnose_environment() -> EnvironmentDiagnosis {
+    #[cfg(target_os = "windows")]
+    let diagnosis = EnvironmentDiagnosis {
+        progman_found: mouse_hook::get_progman_hwnd() != 0,
+        worker_w_found: LAYER_WORKER_W_FOUND.load(Ordering::SeqCst),
+        mouse_hook_installed: HOOK_HANDLE_GLOBAL.load(Ordering::SeqCst) != 0,
+        composition_controller_found: mouse_hook::get_comp_controller_ptr() != 0,
+        session_active: IS_SESSION_ACTIVE.load(Ordering::SeqCst),
+        summary: String::new(),
+    };
+    #[cfg(not(target_os = "windows"))]
+    let diagnosis = EnvironmentDiagnosis {
+        progman_found: false,
+        worker_w_found: false,
+        mouse_hook_installed: false,
+        composition_controller_found: false,
+        session_active: true,
+        summary: String::new(),
+    };
+
+    let problems: Vec<&str> = [
+        (
+            diagnosis.progman_found,
+            "Progman not reachable: the wallpaper can't attach to the desktop",
+        ),
+        (
+            diagnosis.worker_w_found,
+            "WorkerW not found: using the bottom-most window fallback, which can cover icons",
+        ),
+        (
+            diagnosis.mouse_hook_installed,
+            "Mouse hook blocked (often by security software): the wallpaper won't respond to input",
+        ),
+        (
+            diagnosis.composition_controller_found,
+            "WebView2 composition controller missing: input can't be forwarded",
+        ),
+        (diagnosis.session_active, "Session locked or disconnected"),
+    ]
+    .into_iter()
+    .filter(|(ok, _)| !ok)
+    .map(|(_, problem)| problem)
+    .collect();
+
+    EnvironmentDiagnosis {
+        summary: if problems.is_empty() {
+            "All checks passed".into()
+        } else {
+            problems.join("\n")
+        },
+        ..diagnosis
+    }
+}
+
+/// WebView2 runtime version and controller state, for the About page and
+/// support. Stubbed off Windows.
+#[tauri::command]
+pub fn get_webview_info() -> WebViewInfo {
+    #[cfg(target_os = "windows")]
+    {
+        let runtime_version = match wry::webview_version() {
+            Ok(v) => Some(v),
+            Err(e) => {
+                log::warn!("[window_layer] WebView2 runtime version unavailable: {}", e);
+                None
+            }
+        };
+        WebViewInfo {
+            runtime_version,
+            controller_attached: mouse_hook::get_comp_controller_ptr() != 0,
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        WebViewInfo {
+            runtime_version: None,
+            controller_attached: false,
+        }
+    }
+}
+
+/// Grab the rendered wallpaper as PNG bytes. `monitor` indexes the monitors in
+/// enumeration order; `None` captures the whole virtual desktop.
+#[tauri::command]
+pub fn capture_wallpaper(monitor: Option<u32>) -> crate::error::AppResult<tauri::ipc::Response> {
+    #[cfg(target_os = "windows")]
+    {
+        capture_png(monitor).map(tauri::ipc::Response::new)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = monitor;
+        Err(crate::error::AppError::WindowLayer(
+            "Capture is only supported on Windows".into(),
+        ))
+    }
+}
+
+/// Pixels read per dominant color computation; enough for a stable result.
+#[cfg(target_os = "windows")]
+const DOMINANT_COLOR_SAMPLES: usize = 16_384;
+
+static DOMINANT_COLOR: Mutex<Option<crate::commands_core::RgbColor>> = Mutex::new(None);
+
+/// Most common color of the rendered wallpaper, for theming the UI to match.
+/// The last result is cached; pass `refresh` (e.g. after a wallpaper change)
+/// to capture a new frame.
+#[tauri::command]
+pub fn get_wallpaper_dominant_color(
+    refresh: Option<bool>,
+) -> crate::error::AppResult<crate::commands_core::RgbColor> {
+    if !refresh.unwrap_or(false) {
+        if let Some(color) = *DOMINANT_COLOR.lock().unwrap() {
+            return Ok(color);
+        }
+    }
+    #[cfg(target_os = "windows")]
+    {
+        let (rgba, _, _) = capture_rgba(None)?;
+        let color = crate::commands_core::dominant_color(&rgba, DOMINANT_COLOR_SAMPLES)
+            .ok_or_else(|| crate::error::AppError::WindowLayer("Captured image is empty".into()))?;
+        *DOMINANT_COLOR.lock().unwrap() = Some(color);
+        Ok(color)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(crate::error::AppError::WindowLayer(
+            "Capture is only supported on Windows".into(),
+        ))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn record_injection_success(detection: &DesktopDetection) {
+    LAYER_INJECTED.store(true, Ordering::SeqCst);
+    LAYER_WORKER_W_FOUND.store(detection.worker_w_found, Ordering::SeqCst);
+    LAYER_MONITOR_COUNT.store(detection.monitor_count, Ordering::SeqCst);
+    *LAYER_LAST_ERROR.lock().unwrap() = None;
+}
+
+#[cfg(target_os = "windows")]
+fn record_injection_failure(e: &crate::error::AppError) {
+    LAYER_INJECTED.store(false, Ordering::SeqCst);
+    *LAYER_LAST_ERROR.lock().unwrap() = Some(e.to_string());
+}
+
+pub fn restore_desktop_icons_and_unhook() {
+    if !ICONS_RESTORED.swap(true, Ordering::SeqCst) {
+        #[cfg(target_os = "windows")]
+        {
+            use windows::Win32::Foundation::HWND;
+            use windows::Win32::UI::WindowsAndMessaging::{
+                ShowWindow, UnhookWindowsHookEx, HHOOK, SW_HIDE, SW_SHOW,
+            };
+
+            let slv = mouse_hook::get_syslistview_hwnd();
+            if slv != 0 {
+                unsafe {
+                    // ShowWindow returns BOOL (previous visibility state), not Result
+                    let _ = ShowWindow(HWND(slv as *mut _), SW_SHOW);
+                }
+            }
+
+            // A preview leaves a topmost window over everything; hide it
+            // rather than let it linger while the app tears down
+            let wv = mouse_hook::get_webview_hwnd();
+            if PREVIEW_ACTIVE.swap(false, Ordering::SeqCst) && wv != 0 {
+                unsafe {
+                    let _ = ShowWindow(HWND(wv as *mut _), SW_HIDE);
+                }
+                info!("[window_layer] Exited during preview, window hidden");
+            }
+
+            let hook_ptr = HOOK_HANDLE_GLOBAL.swap(0, Ordering::SeqCst);
+            if hook_ptr != 0 {
+                unsafe {
+                    if let Err(e) = UnhookWindowsHookEx(HHOOK(hook_ptr as *mut _)) {
+                        error!("[window_layer] Unhook mouse hook failed: {:?}", e);
+                    }
+                }
+            }
+
+            mouse_hook::remove_leave_hook();
+            mouse_hook::destroy_dispatch_window();
+
+            if wv != 0 {
+                drop_target::revoke(HWND(wv as *mut _));
+            }
+        }
+    }
+}
+
+/// Tear the desktop layer down (hook, dispatch window, cached handles) and
+/// run the launch-time setup again, for a "Repair wallpaper" button that
+/// avoids `restart_app`. The watchdog and explorer watcher keep running; the
+/// layer mode and icon preference are re-applied. Safe to call repeatedly.
+#[tauri::command]
+pub fn reinit_window_layer(_app: tauri::AppHandle) -> crate::error::AppResult<()> {
+    #[cfg(target_os = "windows")]
+    {
+        use tauri::Manager;
+
+        let _guard = REINIT_LOCK.lock().unwrap();
+        let window = _app
+            .get_webview_window("main")
+            .ok_or_else(|| crate::error::AppError::WindowNotFound("main".into()))?;
+        info!("[window_layer] Re-initializing desktop layer");
+
+        apply_desktop_icons_visible(true);
+        let our = mouse_hook::get_webview_hwnd();
+        if our != 0 && current_layer_mode() == LayerMode::Overlay {
+            set_overlay_styles(our, false);
+        }
+        PREVIEW_ACTIVE.store(false, Ordering::SeqCst);
+        mouse_hook::stop_hook_thread();
+        mouse_hook::remove_leave_hook();
+        mouse_hook::destroy_dispatch_window();
+        mouse_hook::reset_state();
+        WATCHDOG_PARENT.store(0, Ordering::SeqCst);
+
+        if let Err(e) = ensure_in_worker_w(&window) {
+            error!("[window_layer] Re-initialization failed: {}", e);
+            record_injection_failure(&e);
+            emit_setup_phase("failed");
+            return Err(e);
+        }
+        let mode = current_layer_mode();
+        if mode != LayerMode::Desktop {
+            apply_layer_mode(mode)?;
+        }
+        if !crate::settings::get().show_desktop_icons {
+            apply_desktop_icons_visible(false);
+        }
+        info!("[window_layer] Desktop layer re-initialized");
+        Ok(())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(crate::error::AppError::WindowLayer(
+            "Re-initialization is only supported on Windows".into(),
+        ))
+    }
+}
+
+/// Re-show the desktop icons if the process dies without a clean exit.
+///
+/// Covers panics (release builds abort right after the hook runs), unhandled
+/// SEH exceptions and console Ctrl+C/close/logoff/shutdown. A hard kill such
+/// as `TerminateProcess` can't be intercepted and leaves hidden icons hidden.
+pub fn install_crash_guard() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic_info| {
+        show_desktop_icons_on_crash();
+        previous(panic_info);
+    }));
+
+    #[cfg(target_os = "windows")]
+    unsafe {
+        use windows::Win32::Foundation::BOOL;
+        use windows::Win32::System::Console::SetConsoleCtrlHandler;
+        use windows::Win32::System::Diagnostics::Debug::{
+            SetUnhandledExceptionFilter, EXCEPTION_POINTERS,
+        };
+
+        unsafe extern "system" fn on_console_ctrl(_ctrl_type: u32) -> BOOL {
+            show_desktop_icons_on_crash();
+            // FALSE: let the default handler terminate the process
+            BOOL(0)
+        }
+
+        unsafe extern "system" fn on_unhandled_exception(_info: *const EXCEPTION_POINTERS) -> i32 {
+            show_desktop_icons_on_crash();
+            // EXCEPTION_CONTINUE_SEARCH: keep the normal crash reporting
+            0
+        }
+
+        if let Err(e) = SetConsoleCtrlHandler(Some(on_console_ctrl), true) {
+            log::warn!("[window_layer] Console ctrl handler not installed: {:?}", e);
+        }
+        SetUnhandledExceptionFilter(Some(on_unhandled_exception));
+    }
+}
+
+/// Crash-path icon restore. Only touches the icon window, asynchronously, so it
+/// can't block on our own (possibly wedged) threads or a hung explorer.
+fn show_desktop_icons_on_crash() {
+    if ICONS_RESTORED.load(Ordering::SeqCst) {
+        return;
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::WindowsAndMessaging::{ShowWindowAsync, SW_SHOW};
+
+        let slv = mouse_hook::get_syslistview_hwnd();
+        if slv != 0 {
+            unsafe {
+                let _ = ShowWindowAsync(HWND(slv as *mut _), SW_SHOW);
+            }
+        }
+    }
+}
+
+// ==============================================================================
+// Windows: Helper Functions
+// ==============================================================================
+
+/// Zero-allocation UTF-16 class name comparison.
+/// CRITICAL for mouse hook performance — avoids heap allocations on the
+/// global Windows input thread where String::from_utf16_lossy would cause
+/// system-wide micro-stutters.
+#[cfg(target_os = "windows")]
+unsafe fn is_class_name(hwnd: windows::Win32::Foundation::HWND, expected: &str) -> bool {
+    use windows::Win32::UI::WindowsAndMessaging::GetClassNameW;
+    let mut buf = [0u16; 128];
+    let len = GetClassNameW(hwnd, &mut buf) as usize;
+    if len != expected.len() {
+        return false;
+    }
+    expected
+        .encode_utf16()
+        .zip(buf[..len].iter())
+        .all(|(a, b)| a == *b)
+}
+
+/// Physical rects of every display monitor, in virtual-screen coordinates.
+#[cfg(target_os = "windows")]
+fn enumerate_monitors() -> Vec<MonitorRect> {
+    use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
+    use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, HDC, HMONITOR};
+
+    unsafe extern "system" fn monitor_enum_cb(
+        _hm: HMONITOR,
+        _hdc: HDC,
+        rect: *mut RECT,
+        lparam: LPARAM,
+    ) -> BOOL {
+        if lparam.0 == 0 || rect.is_null() {
+            return BOOL(1);
+        }
+        let list = &mut *(lparam.0 as *mut Vec<MonitorRect>);
+        let r = rect.read();
+        list.push(MonitorRect {
+            left: r.left,
+            top: r.top,
+            width: r.right - r.left,
+            height: r.bottom - r.top,
+        });
+        BOOL(1)
+    }
+
+    let mut list: Vec<MonitorRect> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(monitor_enum_cb),
+            LPARAM(&mut list as *mut _ as isize),
+        );
+    }
+    list
+}
+
+/// Work areas (monitor minus taskbar and docked app bars), same order as
+/// `enumerate_monitors`.
+#[cfg(target_os = "windows")]
+fn enumerate_work_areas() -> Vec<MonitorRect> {
+    use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
+    use windows::Win32::Graphics::Gdi::{
+        EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO,
+    };
+
+    unsafe extern "system" fn work_area_cb(
+        hm: HMONITOR,
+        _hdc: HDC,
+        rect: *mut RECT,
+        lparam: LPARAM,
+    ) -> BOOL {
+        if lparam.0 == 0 {
+            return BOOL(1);
+        }
+        let list = &mut *(lparam.0 as *mut Vec<MonitorRect>);
+        let mut mi = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        // Keep the order aligned with enumerate_monitors even on failure
+        let r = if GetMonitorInfoW(hm, &mut mi).as_bool() {
+            mi.rcWork
+        } else if !rect.is_null() {
+            rect.read()
+        } else {
+            RECT::default()
+        };
+        list.push(MonitorRect {
+            left: r.left,
+            top: r.top,
+            width: r.right - r.left,
+            height: r.bottom - r.top,
+        });
+        BOOL(1)
+    }
+
+    let mut list: Vec<MonitorRect> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(work_area_cb),
+            LPARAM(&mut list as *mut _ as isize),
+        );
+    }
+    list
+}
+
+/// The rects the wallpaper is laid out on: work areas while the taskbar is
+/// respected, otherwise `monitors` unchanged.
+#[cfg(target_os = "windows")]
+fn layout_rects(monitors: &[MonitorRect]) -> Vec<MonitorRect> {
+    if RESPECT_TASKBAR.load(Ordering::SeqCst) {
+        let work = enumerate_work_areas();
+        if work.len() == monitors.len() {
+            return work;
+        }
+    }
+    monitors.to_vec()
+}
+
+/// Same order as `enumerate_monitors`, plus device name and primary flag.
+#[cfg(target_os = "windows")]
+fn enumerate_monitor_info() -> Vec<MonitorInfo> {
+    use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
+    use windows::Win32::Graphics::Gdi::{
+        EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFOEXW,
+    };
+
+    unsafe extern "system" fn monitor_info_cb(
+        hm: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        lparam: LPARAM,
+    ) -> BOOL {
+        if lparam.0 == 0 {
+            return BOOL(1);
+        }
+        let list = &mut *(lparam.0 as *mut Vec<MonitorInfo>);
+        let mut mi = MONITORINFOEXW::default();
+        mi.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+        if GetMonitorInfoW(hm, &mut mi.monitorInfo).as_bool() {
+            let r = mi.monitorInfo.rcMonitor;
+            let len = mi.szDevice.iter().position(|&c| c == 0).unwrap_or(0);
+            let name = String::from_utf16_lossy(&mi.szDevice[..len]);
+            // MONITORINFOF_PRIMARY
+            let is_primary = mi.monitorInfo.dwFlags & 1 != 0;
+            list.push(MonitorInfo {
+                index: list.len() as u32,
+                id: monitor_interface_path(&mi.szDevice).unwrap_or_else(|| name.clone()),
+                name,
+                left: r.left,
+                top: r.top,
+                width: r.right - r.left,
+                height: r.bottom - r.top,
+                is_primary,
+            });
+        }
+        BOOL(1)
+    }
+
+    let mut list: Vec<MonitorInfo> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(monitor_info_cb),
+            LPARAM(&mut list as *mut _ as isize),
+        );
+    }
+    list
+}
+
+/// Device interface path of the monitor on a display output, e.g.
+/// `\\?\DISPLAY#GSM5B09#...#{e6f07b5f-...}`. Unlike `\\.\DISPLAYn` it
+/// identifies the physical monitor, so it survives reboots and reconnects.
+#[cfg(target_os = "windows")]
+unsafe fn monitor_interface_path(device: &[u16; 32]) -> Option<String> {
+    use windows::Win32::Graphics::Gdi::{EnumDisplayDevicesW, DISPLAY_DEVICEW};
+    use windows::Win32::UI::WindowsAndMessaging::EDD_GET_DEVICE_INTERFACE_NAME;
+
+    let mut dd = DISPLAY_DEVICEW {
+        cb: std::mem::size_of::<DISPLAY_DEVICEW>() as u32,
+        ..Default::default()
+    };
+    if !EnumDisplayDevicesW(
+        windows::core::PCWSTR(device.as_ptr()),
+        0,
+        &mut dd,
+        EDD_GET_DEVICE_INTERFACE_NAME,
+    )
+    .as_bool()
+    {
+        return None;
+    }
+    let len = dd.DeviceID.iter().position(|&c| c == 0).unwrap_or(0);
+    (len > 0).then(|| String::from_utf16_lossy(&dd.DeviceID[..len]))
+}
+
+/// Same order as `enumerate_monitors`; monitors whose DPI can't be read report 1.0.
+#[cfg(target_os = "windows")]
+fn enumerate_monitor_dpi() -> Vec<MonitorDpi> {
+    use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
+    use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, HDC, HMONITOR};
+    use windows::Win32::UI::HiDpi::{GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+
+    unsafe extern "system" fn monitor_dpi_cb(
+        hm: HMONITOR,
+        _hdc: HDC,
+        _rect: *mut RECT,
+        lparam: LPARAM,
+    ) -> BOOL {
+        if lparam.0 == 0 {
+            return BOOL(1);
+        }
+        let list = &mut *(lparam.0 as *mut Vec<MonitorDpi>);
+        let (mut dpi_x, mut dpi_y) = (96u32, 96u32);
+        let _ = GetDpiForMonitor(hm, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y);
+        list.push(MonitorDpi {
+            index: list.len() as u32,
+            scale_factor: dpi_x as f64 / 96.0,
+        });
+        BOOL(1)
+    }
+
+    let mut list: Vec<MonitorDpi> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(monitor_dpi_cb),
+            LPARAM(&mut list as *mut _ as isize),
+        );
+    }
+    list
+}
+
+/// Called from the dispatch window on WM_DPICHANGED. That only reaches us for
+/// the dispatch window's monitor, so display changes re-emit as well.
+#[cfg(target_os = "windows")]
+fn on_dpi_change() {
+    std::thread::spawn(|| {
+        use crate::events::{AppEvent, EmitAppEvent};
+
+        let monitors = enumerate_monitor_dpi();
+        info!("[window_layer] DPI change: {:?}", monitors);
+        if let Some(app) = APP_HANDLE.get() {
+            let _ = app.emit_app_event(&AppEvent::MonitorDpiChanged { monitors });
+        }
+    });
+}
+
+/// Called from the dispatch window on the "ImmersiveColorSet" WM_SETTINGCHANGE.
+/// Off the dispatch thread, which is also forwarding input.
+#[cfg(target_os = "windows")]
+fn on_theme_change() {
+    std::thread::spawn(|| {
+        if let Some(app) = APP_HANDLE.get() {
+            crate::theme::refresh(app);
+        }
+    });
+}
+
+/// Current AC line (0 battery, 1 AC) and charge percent, the latter `None`
+/// when Windows reports it as unknown. `None` without a system battery.
+#[cfg(target_os = "windows")]
+fn read_power_status() -> Option<(u8, Option<u8>)> {
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // BatteryFlag 128: no system battery; 255: unknown
+    const NO_BATTERY: u8 = 128;
+    const UNKNOWN_PERCENT: u8 = 255;
+
+    let mut status = SYSTEM_POWER_STATUS::default();
+    if unsafe { GetSystemPowerStatus(&mut status) }.is_err()
+        || status.ACLineStatus > 1
+        || status.BatteryFlag & NO_BATTERY != 0
+    {
+        return None;
+    }
+    let percent =
+        (status.BatteryLifePercent != UNKNOWN_PERCENT).then(|| status.BatteryLifePercent.min(100));
+    Some((status.ACLineStatus, percent))
+}
+
+/// Record the AC line at startup so the first status change after it is
+/// compared against the real state rather than "unknown".
+#[cfg(target_os = "windows")]
+fn seed_power_status() {
+    if let Some((ac_line, _)) = read_power_status() {
+        LAST_AC_LINE.store(ac_line, Ordering::SeqCst);
+    }
+}
+
+/// Called from the dispatch window on PBT_APMPOWERSTATUSCHANGE, which also
+/// fires for every percent of charge: only plug/unplug is emitted. The battery
+/// category of `system-data-update` stays the source of truth for the level.
+#[cfg(target_os = "windows")]
+fn on_power_status_change() {
+    use crate::events::{AppEvent, EmitAppEvent};
+
+    let Some((ac_line, percent)) = read_power_status() else {
+        return;
+    };
+    if LAST_AC_LINE.swap(ac_line, Ordering::SeqCst) == ac_line {
+        return;
+    }
+    let charging = ac_line == 1;
+    let level = percent.map(|p| p as f32 / 100.0);
+    info!(
+        "[window_layer] Power: {} ({})",
+        if charging { "plugged in" } else { "on battery" },
+        percent.map_or("unknown level".into(), |p| format!("{}%", p))
+    );
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit_app_event(&AppEvent::PowerStateChanged { charging, level });
+        crate::frame_rate::refresh(app);
+    }
+}
+
+/// Recompute and cache the virtual desktop bounds.
+#[cfg(target_os = "windows")]
+fn refresh_virtual_bounds(monitors: &[MonitorRect]) -> crate::commands_core::ScreenRect {
+    let b = MonitorBounds::union(monitors);
+    let rect = crate::commands_core::ScreenRect {
+        left: b.left,
+        top: b.top,
+        width: b.right - b.left,
+        height: b.bottom - b.top,
+    };
+    *VIRTUAL_BOUNDS.lock().unwrap() = Some(rect);
+    rect
+}
+
+/// Bounding box of all monitors (the virtual desktop).
+#[cfg(target_os = "windows")]
+struct MonitorBounds {
+    left: i32,
+    top: i32,
+    right: i32,
+    bottom: i32,
+}
+
+#[cfg(target_os = "windows")]
+impl MonitorBounds {
+    fn union(monitors: &[MonitorRect]) -> Self {
+        let mut b = Self {
+            left: i32::MAX,
+            top: i32::MAX,
+            right: i32::MIN,
+            bottom: i32::MIN,
+        };
+        for m in monitors {
+            b.left = b.left.min(m.left);
+            b.top = b.top.min(m.top);
+            b.right = b.right.max(m.left + m.width);
+            b.bottom = b.bottom.max(m.top + m.height);
+        }
+        b
+    }
+
+    /// The selected wallpaper monitor, or the whole virtual desktop when none
+    /// is selected (or the selection no longer exists). Work areas only while
+    /// the taskbar is respected.
+    fn render(monitors: &[MonitorRect]) -> Self {
+        let rects = layout_rects(monitors);
+        let selected = WALLPAPER_MONITOR.load(Ordering::SeqCst) as usize;
+        match rects.get(selected) {
+            Some(m) => Self::union(std::slice::from_ref(m)),
+            None => Self::union(&rects),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn capture_png(monitor: Option<u32>) -> crate::error::AppResult<Vec<u8>> {
+    let (rgba, width, height) = capture_rgba(monitor)?;
+    crate::commands_core::encode_png(&rgba, width, height)
+}
+
+/// Capture as RGBA plus its size. PrintWindow with PW_RENDERFULLCONTENT so
+/// DirectComposition (WebView2) content is included; plain BitBlt of the
+/// window DC yields black.
+#[cfg(target_os = "windows")]
+fn capture_rgba(monitor: Option<u32>) -> crate::error::AppResult<(Vec<u8>, u32, u32)> {
+    use crate::commands_core::crop_bgra_to_rgba;
+    use crate::error::AppError;
+    use windows::Win32::Foundation::{HWND, RECT};
+    use windows::Win32::Graphics::Gdi::*;
+    use windows::Win32::Storage::Xps::{PrintWindow, PRINT_WINDOW_FLAGS};
+    use windows::Win32::UI::WindowsAndMessaging::GetClientRect;
+
+    // Not exposed by the metadata as a named constant
+    const PW_RENDERFULLCONTENT: PRINT_WINDOW_FLAGS = PRINT_WINDOW_FLAGS(2);
+
+    let raw = mouse_hook::get_webview_hwnd();
+    if raw == 0 {
+        return Err(AppError::WindowNotFound("wallpaper webview".into()));
+    }
+    let hwnd = HWND(raw as *mut _);
+
+    unsafe {
+        let mut rc = RECT::default();
+        GetClientRect(hwnd, &mut rc)
+            .map_err(|e| AppError::WindowLayer(format!("GetClientRect failed: {}", e)))?;
+        let (w, h) = (rc.right - rc.left, rc.bottom - rc.top);
+        if w <= 0 || h <= 0 {
+            return Err(AppError::WindowLayer("Wallpaper window has no size".into()));
+        }
+
+        // The window covers the render bounds (one monitor or the whole desktop)
+        let area = match monitor {
+            None => (0, 0, w as u32, h as u32),
+            Some(i) => {
+                let monitors = layout_rects(&enumerate_monitors());
+                let m = monitors.get(i as usize).ok_or_else(|| {
+                    AppError::Validation(format!("Monitor index {} out of range", i))
+                })?;
+                let origin = MonitorBounds::render(&monitors);
+                (
+                    (m.left - origin.left).max(0) as u32,
+                    (m.top - origin.top).max(0) as u32,
+                    m.width as u32,
+                    m.height as u32,
+                )
+            }
+        };
+
+        let screen_dc = GetDC(HWND::default());
+        let mem_dc = CreateCompatibleDC(screen_dc);
+        let bmi = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: w,
+                biHeight: -h, // top-down
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut bits: *mut std::ffi::c_void = std::ptr::null_mut();
+        let dib = CreateDIBSection(mem_dc, &bmi, DIB_RGB_COLORS, &mut bits, None, 0);
+
+        let result = match dib {
+            Ok(dib) if !bits.is_null() => {
+                let old = SelectObject(mem_dc, dib);
+                let printed = PrintWindow(hwnd, mem_dc, PW_RENDERFULLCONTENT).as_bool();
+                let _ = GdiFlush();
+                let out = if printed {
+                    let pixels =
+                        std::slice::from_raw_parts(bits as *const u8, w as usize * h as usize * 4);
+                    crop_bgra_to_rgba(pixels, w as u32, h as u32, area)
+                } else {
+                    Err(AppError::WindowLayer("PrintWindow failed".into()))
+                };
+                SelectObject(mem_dc, old);
+                let _ = DeleteObject(dib);
+                out
+            }
+            _ => Err(AppError::WindowLayer("CreateDIBSection failed".into())),
+        };
+
+        let _ = DeleteDC(mem_dc);
+        ReleaseDC(HWND::default(), screen_dc);
+
+        Ok((result?, area.2, area.3))
+    }
+}
+
+// ==============================================================================
+// Windows: Desktop Detection
+// ==============================================================================
+
+#[cfg(target_os = "windows")]
+struct DesktopDetection {
+    progman: windows::Win32::Foundation::HWND,
+    explorer_pid: u32,
+    target_parent: windows::Win32::Foundation::HWND,
+    syslistview: windows::Win32::Foundation::HWND,
+    /// Sibling window that must stay IN FRONT of target_parent (Z-order).
+    /// Win11 24H2+: SHELLDLL_DefView (child of Progman).
+    /// Legacy: WorkerW that contains SHELLDLL_DefView.
+    zorder_anchor: windows::Win32::Foundation::HWND,
+    /// False when no WorkerW exists and we fell back to Progman as parent.
+    worker_w_found: bool,
+    monitor_count: u32,
+    /// Wallpaper rect in target_parent client coordinates (the parent's
+    /// origin is the virtual desktop's top-left).
+    v_x: i32,
+    v_y: i32,
+    v_width: i32,
+    v_height: i32,
+}
+
+#[cfg(target_os = "windows")]
+fn detect_desktop() -> Result<DesktopDetection, crate::error::AppError> {
+    use crate::error::AppError;
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    unsafe {
+        let progman = FindWindowW(windows::core::w!("Progman"), None)
+            .map_err(|_| AppError::WindowLayer("Could not find Progman".into()))?;
+
+        let mut explorer_pid: u32 = 0;
+        GetWindowThreadProcessId(progman, Some(&mut explorer_pid));
+
+        // Force Windows to spawn the wallpaper WorkerW layer.
+        // This is an undocumented Progman message discovered via reverse engineering;
+        // it triggers creation of the WorkerW window behind the desktop icons.
+        const PROGMAN_SPAWN_WORKERW: u32 = 0x052C;
+        let mut msg_result: usize = 0;
+        let _ = SendMessageTimeoutW(
+            progman,
+            PROGMAN_SPAWN_WORKERW,
+            WPARAM(0x0D),
+            LPARAM(1),
+            SMTO_NORMAL,
+            1000,
+            Some(&mut msg_result),
+        );
+        std::thread::sleep(std::time::Duration::from_millis(150));
+
+        let mut target_parent;
+        let mut shell_for_slv;
+
+        // 1. Detection Win11 24H2+: SHELLDLL_DefView is direct child of Progman
+        let shell_view = FindWindowExW(
+            progman,
+            HWND::default(),
+            windows::core::w!("SHELLDLL_DefView"),
+            None,
+        )
+        .unwrap_or_default();
+
+        if !shell_view.is_invalid() {
+            target_parent =
+                FindWindowExW(progman, HWND::default(), windows::core::w!("WorkerW"), None)
+                    .unwrap_or_default();
+            shell_for_slv = shell_view;
+        } else {
+            // 2. Fallback Win10/Win11
+            struct SearchData {
+                parent: HWND,
+                sv: HWND,
+            }
+            let mut data = SearchData {
+                parent: HWND::default(),
+                sv: HWND::default(),
+            };
+
+            unsafe extern "system" fn enum_cb(hwnd: HWND, lp: LPARAM) -> BOOL {
+                if lp.0 == 0 {
+                    return BOOL(0);
+                }
+                let sv = FindWindowExW(
+                    hwnd,
+                    HWND::default(),
+                    windows::core::w!("SHELLDLL_DefView"),
+                    None,
+                )
+                .unwrap_or_default();
+                if !sv.is_invalid() {
+                    let d = &mut *(lp.0 as *mut SearchData);
+                    d.sv = sv;
+                    d.parent =
+                        FindWindowExW(HWND::default(), hwnd, windows::core::w!("WorkerW"), None)
+                            .unwrap_or_default();
+                    return BOOL(0);
+                }
+                BOOL(1)
+            }
+            let _ = EnumWindows(Some(enum_cb), LPARAM(&mut data as *mut _ as isize));
+            target_parent = data.parent;
+            shell_for_slv = data.sv;
+        }
+
+        // Compute Z-order anchor: the sibling that must stay IN FRONT
+        // of target_parent so WindowFromPoint returns SysListView32.
+        let zorder_anchor = if !shell_view.is_invalid() {
+            // Win11 24H2+: SHELLDLL_DefView is a direct sibling of WorkerW
+            shell_view
+        } else if !shell_for_slv.is_invalid() {
+            // Legacy: SHELLDLL_DefView is inside WorkerW A; get WorkerW A
+            GetParent(shell_for_slv).unwrap_or_default()
+        } else {
+            HWND::default()
+        };
+
+        let worker_w_found = !target_parent.is_invalid();
+        if !worker_w_found {
+            target_parent = progman;
+        }
+
+        let mut syslistview = HWND::default();
+        unsafe extern "system" fn find_slv(hwnd: HWND, lp: LPARAM) -> BOOL {
+            if lp.0 == 0 {
+                return BOOL(0);
+            }
             if is_class_name(hwnd, "SysListView32") {
                 *(lp.0 as *mut HWND) = hwnd;
                 return BOOL(0);
@@ -240,41 +1724,10 @@ fn detect_desktop() -> Result<DesktopDetection, crate::error::AppError> {
         }
 
         // Absolute Physical Bounds
-        struct MonitorRects {
-            left: i32,
-            top: i32,
-            right: i32,
-            bottom: i32,
-        }
-        let mut m_rects = MonitorRects {
-            left: i32::MAX,
-            top: i32::MAX,
-            right: i32::MIN,
-            bottom: i32::MIN,
-        };
-        unsafe extern "system" fn monitor_enum_cb(
-            _hm: HMONITOR,
-            _hdc: HDC,
-            rect: *mut RECT,
-            lparam: LPARAM,
-        ) -> BOOL {
-            if lparam.0 == 0 || rect.is_null() {
-                return BOOL(1);
-            }
-            let data = &mut *(lparam.0 as *mut MonitorRects);
-            let r = rect.read();
-            data.left = data.left.min(r.left);
-            data.top = data.top.min(r.top);
-            data.right = data.right.max(r.right);
-            data.bottom = data.bottom.max(r.bottom);
-            BOOL(1)
-        }
-        let _ = EnumDisplayMonitors(
-            HDC::default(),
-            None,
-            Some(monitor_enum_cb),
-            LPARAM(&mut m_rects as *mut _ as isize),
-        );
+        let monitors = enumerate_monitors();
+        let virtual_bounds = MonitorBounds::union(&monitors);
+        let m_rects = MonitorBounds::render(&monitors);
+        refresh_virtual_bounds(&monitors);
 
         let width = m_rects.right - m_rects.left;
         let height = m_rects.bottom - m_rects.top;
@@ -289,6 +1742,10 @@ fn detect_desktop() -> Result<DesktopDetection, crate::error::AppError> {
             target_parent,
             syslistview,
             zorder_anchor,
+            worker_w_found,
+            monitor_count: monitors.len() as u32,
+            v_x: m_rects.left - virtual_bounds.left,
+            v_y: m_rects.top - virtual_bounds.top,
             v_width: width,
             v_height: height,
         })
@@ -307,287 +1764,1043 @@ fn detect_desktop() -> Result<DesktopDetection, crate::error::AppError> {
 const NCCALC_SUBCLASS_ID: usize = 0xDEAD_BEE0;
 
 #[cfg(target_os = "windows")]
-unsafe extern "system" fn nccalc_subclass_proc(
-    hwnd: windows::Win32::Foundation::HWND,
-    msg: u32,
-    wparam: windows::Win32::Foundation::WPARAM,
-    lparam: windows::Win32::Foundation::LPARAM,
-    uid_subclass: usize,
-    _ref_data: usize,
-) -> windows::Win32::Foundation::LRESULT {
-    use windows::Win32::Foundation::LRESULT;
-    use windows::Win32::UI::Shell::{DefSubclassProc, RemoveWindowSubclass};
-    use windows::Win32::UI::WindowsAndMessaging::{WM_NCCALCSIZE, WM_NCDESTROY};
+unsafe extern "system" fn nccalc_subclass_proc(
+    hwnd: windows::Win32::Foundation::HWND,
+    msg: u32,
+    wparam: windows::Win32::Foundation::WPARAM,
+    lparam: windows::Win32::Foundation::LPARAM,
+    uid_subclass: usize,
+    _ref_data: usize,
+) -> windows::Win32::Foundation::LRESULT {
+    use windows::Win32::Foundation::LRESULT;
+    use windows::Win32::UI::Shell::{DefSubclassProc, RemoveWindowSubclass};
+    use windows::Win32::UI::WindowsAndMessaging::{WM_NCCALCSIZE, WM_NCDESTROY};
+
+    match msg {
+        WM_NCCALCSIZE => LRESULT(0), // Zero non-client area
+        WM_NCDESTROY => {
+            let _ = RemoveWindowSubclass(hwnd, Some(nccalc_subclass_proc), uid_subclass);
+            DefSubclassProc(hwnd, msg, wparam, lparam)
+        }
+        _ => DefSubclassProc(hwnd, msg, wparam, lparam),
+    }
+}
+
+/// Drop the WM_NCCALCSIZE subclass when the window leaves the desktop, so a
+/// detached window gets normal frame handling. `apply_injection` adds it back.
+#[cfg(target_os = "windows")]
+fn remove_nccalc_subclass(hwnd: windows::Win32::Foundation::HWND) {
+    unsafe {
+        let _ = windows::Win32::UI::Shell::RemoveWindowSubclass(
+            hwnd,
+            Some(nccalc_subclass_proc),
+            NCCALC_SUBCLASS_ID,
+        );
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn apply_injection(our_hwnd: windows::Win32::Foundation::HWND, detection: &DesktopDetection) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    if !detection.worker_w_found {
+        place_bottom_fallback(our_hwnd);
+        return;
+    }
+
+    unsafe {
+        if GetParent(our_hwnd).unwrap_or_default() == detection.target_parent {
+            // Already injected: only follow virtual desktop size changes
+            let _ = SetWindowPos(
+                our_hwnd,
+                HWND::default(),
+                detection.v_x,
+                detection.v_y,
+                detection.v_width,
+                detection.v_height,
+                SWP_NOZORDER | SWP_NOACTIVATE,
+            );
+            return;
+        }
+
+        // Whatever the user was typing into must keep focus through injection
+        let previous_foreground = GetForegroundWindow();
+
+        // 1. Strip ALL frame / border styles
+        let mut style = GetWindowLongW(our_hwnd, GWL_STYLE) as u32;
+        style &= !(WS_THICKFRAME.0
+            | WS_CAPTION.0
+            | WS_SYSMENU.0
+            | WS_MAXIMIZEBOX.0
+            | WS_MINIMIZEBOX.0
+            | WS_POPUP.0
+            | WS_BORDER.0
+            | WS_DLGFRAME.0);
+        style |= WS_CHILD.0 | WS_VISIBLE.0;
+        let _ = SetWindowLongW(our_hwnd, GWL_STYLE, style as i32);
+
+        // WS_EX_NOACTIVATE (and TOOLWINDOW, to stay out of Alt-Tab) is held
+        // until the reparent completes; as a child it's stripped again below
+        let mut ex_style = GetWindowLongW(our_hwnd, GWL_EXSTYLE) as u32;
+        ex_style &= !(WS_EX_LAYERED.0
+            | WS_EX_APPWINDOW.0
+            | WS_EX_CLIENTEDGE.0
+            | WS_EX_WINDOWEDGE.0
+            | WS_EX_DLGMODALFRAME.0
+            | WS_EX_STATICEDGE.0);
+        ex_style |= WS_EX_NOACTIVATE.0 | WS_EX_TOOLWINDOW.0;
+        let _ = SetWindowLongW(our_hwnd, GWL_EXSTYLE, ex_style as i32);
+
+        // 2. WM_NCCALCSIZE subclass → zero non-client area (once: a window
+        // detached and re-injected still carries it unless removed)
+        use windows::Win32::UI::Shell::{GetWindowSubclass, SetWindowSubclass};
+        if !GetWindowSubclass(
+            our_hwnd,
+            Some(nccalc_subclass_proc),
+            NCCALC_SUBCLASS_ID,
+            None,
+        )
+        .as_bool()
+        {
+            let _ = SetWindowSubclass(our_hwnd, Some(nccalc_subclass_proc), NCCALC_SUBCLASS_ID, 0);
+        }
+
+        // 3. Kill DWM border rendering
+        use windows::Win32::Graphics::Dwm::*;
+        let color_none: u32 = 0xFFFFFFFE; // DWMWA_COLOR_NONE
+        let no_round: i32 = 1; // DWMWCP_DONOTROUND
+        let _ = DwmSetWindowAttribute(
+            our_hwnd,
+            DWMWA_BORDER_COLOR,
+            &color_none as *const _ as *const _,
+            std::mem::size_of::<u32>() as u32,
+        );
+        let _ = DwmSetWindowAttribute(
+            our_hwnd,
+            DWMWA_WINDOW_CORNER_PREFERENCE,
+            &no_round as *const _ as *const _,
+            std::mem::size_of::<i32>() as u32,
+        );
+
+        // 4. Background brush (black unless set_background_color chose one)
+        SetClassLongPtrW(our_hwnd, GCLP_HBRBACKGROUND, background_brush());
+
+        // 5. Reparent into WorkerW (SW_SHOWNA preserves Z-order)
+        let _ = ShowWindow(detection.target_parent, SW_SHOWNA);
+        let _ = SetParent(our_hwnd, detection.target_parent);
+
+        // 6. Size to full monitor + force frame recalc
+        let _ = SetWindowPos(
+            our_hwnd,
+            HWND::default(),
+            detection.v_x,
+            detection.v_y,
+            detection.v_width,
+            detection.v_height,
+            SWP_FRAMECHANGED | SWP_SHOWWINDOW | SWP_NOZORDER | SWP_NOACTIVATE,
+        );
+        let _ = ShowWindow(our_hwnd, SW_SHOWNOACTIVATE);
+
+        // Reparented: the WebView host needs activation for keyboard input
+        let ex_style = GetWindowLongW(our_hwnd, GWL_EXSTYLE) as u32 & !WS_EX_NOACTIVATE.0;
+        let _ = SetWindowLongW(our_hwnd, GWL_EXSTYLE, ex_style as i32);
+
+        // 7. Ensure WorkerW is BEHIND the icon layer so WindowFromPoint
+        //    returns SysListView32, enabling fully native icon interactions
+        //    (drag & drop, double-click, context menus, selection rectangle).
+        if !detection.zorder_anchor.is_invalid()
+            && detection.zorder_anchor != detection.target_parent
+        {
+            let _ = SetWindowPos(
+                detection.target_parent,
+                detection.zorder_anchor,
+                0,
+                0,
+                0,
+                0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            );
+        }
+
+        let foreground = GetForegroundWindow();
+        if !previous_foreground.is_invalid() && foreground != previous_foreground {
+            log::warn!(
+                "[apply_injection] Foreground changed during injection (0x{:X} -> 0x{:X}), handing it back",
+                previous_foreground.0 as isize,
+                foreground.0 as isize
+            );
+            if IsWindow(previous_foreground).as_bool() {
+                let _ = SetForegroundWindow(previous_foreground);
+            }
+        }
+
+        info!(
+            "[apply_injection] Done. Parent=0x{:X}, Size={}x{}",
+            detection.target_parent.0 as isize, detection.v_width, detection.v_height
+        );
+    }
+}
+
+// ==============================================================================
+// Windows: Initialization
+// ==============================================================================
+
+/// One mouse-hook target per monitor the wallpaper covers. A single WebView
+/// still backs all of them, so every entry shares the same window and parent.
+#[cfg(target_os = "windows")]
+fn register_hook_targets(
+    webview: windows::Win32::Foundation::HWND,
+    parent: windows::Win32::Foundation::HWND,
+) {
+    use windows::Win32::Foundation::RECT;
+
+    let target = |rect| mouse_hook::HookTarget {
+        webview: webview.0 as isize,
+        parent: parent.0 as isize,
+        controller: 0,
+        rect,
+    };
+    // Work areas while the taskbar is respected, so input over it isn't forwarded
+    let monitors = layout_rects(&enumerate_monitors());
+    let selected = WALLPAPER_MONITOR.load(Ordering::SeqCst) as usize;
+    let covered = match monitors.get(selected) {
+        Some(m) => std::slice::from_ref(m),
+        None => &monitors[..],
+    };
+    let mut targets: Vec<_> = covered
+        .iter()
+        .map(|m| {
+            target(RECT {
+                left: m.left,
+                top: m.top,
+                right: m.left + m.width,
+                bottom: m.top + m.height,
+            })
+        })
+        .collect();
+    if targets.is_empty() {
+        // An empty rect matches every point in target_at
+        targets.push(target(RECT::default()));
+    }
+    mouse_hook::set_targets(targets);
+}
+
+/// Record a setup milestone and tell the frontend, so it can show a splash
+/// instead of a black screen while injection is in progress.
+#[cfg(target_os = "windows")]
+fn emit_setup_phase(phase: &'static str) {
+    use crate::events::{AppEvent, EmitAppEvent};
+
+    *SETUP_PHASE.lock().unwrap() = phase;
+    info!("[window_layer] Setup phase: {}", phase);
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit_app_event(&AppEvent::SetupProgress {
+            phase: phase.to_string(),
+        });
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn ensure_in_worker_w(window: &tauri::WebviewWindow) -> crate::error::AppResult<()> {
+    use windows::Win32::Foundation::HWND;
+
+    let _ = window.set_ignore_cursor_events(false);
+    let our_hwnd_raw = window.hwnd()?;
+    let our_hwnd = HWND(our_hwnd_raw.0 as *mut _);
+
+    emit_setup_phase("detecting");
+    let detection = detect_desktop()?;
+
+    mouse_hook::set_webview_hwnd(our_hwnd.0 as isize);
+    register_hook_targets(our_hwnd, detection.target_parent);
+    mouse_hook::set_progman_hwnd(detection.progman.0 as isize);
+    mouse_hook::set_explorer_pid(detection.explorer_pid);
+    if !detection.syslistview.is_invalid() {
+        mouse_hook::set_syslistview_hwnd(detection.syslistview.0 as isize);
+    }
+
+    emit_setup_phase("injecting");
+    apply_injection(our_hwnd, &detection);
+    record_injection_success(&detection);
+    seed_power_status();
+    mouse_hook::init_dispatch_window();
+    drop_target::register(our_hwnd);
+
+    let (x, y, w, h) = (
+        detection.v_x,
+        detection.v_y,
+        detection.v_width,
+        detection.v_height,
+    );
+    let our_hwnd_isize = our_hwnd.0 as isize;
+
+    emit_setup_phase("waiting-controller");
+    // Before the wait thread so "ready" can't be emitted ahead of "hook-started";
+    // the hook only forwards input once the controller pointer is set
+    mouse_hook::start_hook_thread();
+    emit_setup_phase("hook-started");
+
+    let generation = CONTROLLER_WAIT_GEN.fetch_add(1, Ordering::SeqCst) + 1;
+    std::thread::spawn(move || wait_for_controller(generation, our_hwnd_isize, (x, y, w, h)));
+
+    // Zombie window watchdog: re-detects desktop if parent HWND becomes stale
+    WATCHDOG_PARENT.store(detection.target_parent.0 as isize, Ordering::SeqCst);
+    let watchdog_our = our_hwnd.0 as isize;
+    if !WATCHDOG_RUNNING.swap(true, Ordering::SeqCst) {
+        std::thread::spawn(move || watchdog_loop(watchdog_our));
+    }
+
+    if !EXPLORER_WATCHER_RUNNING.swap(true, Ordering::SeqCst) {
+        start_explorer_watcher(our_hwnd.0 as isize, detection.explorer_pid);
+    }
+
+    Ok(())
+}
+
+/// Poll interval while waiting for the composition controller at startup.
+#[cfg(target_os = "windows")]
+const CONTROLLER_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(10);
+/// Poll interval once the initial wait has timed out (slow boots, loaded machines).
+#[cfg(target_os = "windows")]
+const CONTROLLER_SLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+/// Bounds for the `controllerTimeoutMs` setting.
+#[cfg(target_os = "windows")]
+const CONTROLLER_TIMEOUT_RANGE_MS: std::ops::RangeInclusive<u64> = 100..=60_000;
+
+/// Wait for WebView2's composition controller, then size the window and its
+/// children so input forwarding works. Past the `controllerTimeoutMs` setting
+/// this reports "controller-timeout" and keeps polling at a slower cadence, so
+/// a late controller still ends in "ready" instead of a dead wallpaper.
+#[cfg(target_os = "windows")]
+fn wait_for_controller(generation: u64, our_hwnd: isize, (x, y, w, h): (i32, i32, i32, i32)) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::*;
+
+    let timeout_ms = crate::settings::get().controller_timeout_ms.clamp(
+        *CONTROLLER_TIMEOUT_RANGE_MS.start(),
+        *CONTROLLER_TIMEOUT_RANGE_MS.end(),
+    );
+    let started = std::time::Instant::now();
+    let mut timed_out = false;
+    let wv_h = HWND(our_hwnd as *mut _);
+    loop {
+        if CONTROLLER_WAIT_GEN.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        let ptr = wry::get_last_composition_controller_ptr();
+        if ptr != 0 {
+            mouse_hook::set_comp_controller_ptr(ptr);
+            unsafe {
+                let _ = SetWindowPos(
+                    wv_h,
+                    HWND::default(),
+                    x,
+                    y,
+                    w,
+                    h,
+                    SWP_NOZORDER | SWP_SHOWWINDOW | SWP_FRAMECHANGED | SWP_NOACTIVATE,
+                );
+            }
+            resize_webview(wv_h, w, h);
+            if timed_out {
+                info!(
+                    "[window_layer] Composition controller ready after {:.1}s",
+                    started.elapsed().as_secs_f32()
+                );
+            }
+            emit_setup_phase("ready");
+            return;
+        }
+        if !timed_out && started.elapsed().as_millis() as u64 >= timeout_ms {
+            timed_out = true;
+            error!(
+                "[window_layer] Timed out waiting for composition controller ({}ms), still retrying",
+                timeout_ms
+            );
+            emit_setup_phase("controller-timeout");
+        }
+        if timed_out && !unsafe { IsWindow(wv_h) }.as_bool() {
+            return;
+        }
+        std::thread::sleep(if timed_out {
+            CONTROLLER_SLOW_POLL_INTERVAL
+        } else {
+            CONTROLLER_POLL_INTERVAL
+        });
+    }
+}
+
+/// Ticks every second so interval changes (and pausing) apply immediately
+/// instead of after a full sleep of the previous interval.
+#[cfg(target_os = "windows")]
+fn watchdog_loop(our_hwnd: isize) {
+    use std::time::Duration;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::IsWindow;
+
+    let mut elapsed = 0u64;
+    loop {
+        std::thread::sleep(Duration::from_secs(1));
+        let interval = WATCHDOG_INTERVAL_SECS.load(Ordering::SeqCst);
+        if interval == 0 {
+            elapsed = 0;
+            continue;
+        }
+        elapsed += 1;
+        if elapsed < interval {
+            continue;
+        }
+        elapsed = 0;
+
+        let parent_raw = WATCHDOG_PARENT.load(Ordering::SeqCst);
+        if parent_raw == 0 {
+            continue;
+        }
+        unsafe {
+            if !IsWindow(HWND(parent_raw as *mut _)).as_bool() {
+                info!("[watchdog] Parent HWND stale, re-detecting desktop...");
+                if let Err(e) = reinject(our_hwnd) {
+                    error!("[watchdog] Re-detection failed: {}", e);
+                    record_injection_failure(&e);
+                }
+            }
+        }
+    }
+}
+
+// ==============================================================================
+// Windows: Re-injection (watchdog + explorer restarts)
+// ==============================================================================
+
+/// Re-detect the desktop and re-inject our window into the new WorkerW.
+/// Shared by the periodic watchdog and the explorer-restart watcher.
+#[cfg(target_os = "windows")]
+fn reinject(our_hwnd: isize) -> crate::error::AppResult<()> {
+    use windows::Win32::Foundation::HWND;
 
-    match msg {
-        WM_NCCALCSIZE => LRESULT(0), // Zero non-client area
-        WM_NCDESTROY => {
-            let _ = RemoveWindowSubclass(hwnd, Some(nccalc_subclass_proc), uid_subclass);
-            DefSubclassProc(hwnd, msg, wparam, lparam)
+    // Watchdogs must not pull a detached or previewed window back into the desktop
+    if current_layer_mode() != LayerMode::Desktop || PREVIEW_ACTIVE.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+
+    let d = detect_desktop()?;
+    register_hook_targets(HWND(our_hwnd as *mut _), d.target_parent);
+    mouse_hook::set_progman_hwnd(d.progman.0 as isize);
+    mouse_hook::set_explorer_pid(d.explorer_pid);
+    mouse_hook::clear_desktop_core_hwnd();
+    if !d.syslistview.is_invalid() {
+        mouse_hook::set_syslistview_hwnd(d.syslistview.0 as isize);
+        // A fresh shell shows its icons again; re-apply the user's preference
+        if !crate::settings::get().show_desktop_icons {
+            apply_desktop_icons_visible(false);
         }
-        _ => DefSubclassProc(hwnd, msg, wparam, lparam),
     }
+    apply_injection(HWND(our_hwnd as *mut _), &d);
+    record_injection_success(&d);
+    WATCHDOG_PARENT.store(d.target_parent.0 as isize, Ordering::SeqCst);
+    info!("[window_layer] Re-injection done");
+    Ok(())
 }
 
+/// Strip borders from the WebView's child HWNDs, give them the background
+/// brush and size them to `w`x`h`, then resize the WebView2 controller to match.
 #[cfg(target_os = "windows")]
-fn apply_injection(our_hwnd: windows::Win32::Foundation::HWND, detection: &DesktopDetection) {
-    use windows::Win32::Foundation::HWND;
+fn resize_webview(our_hwnd: windows::Win32::Foundation::HWND, w: i32, h: i32) {
+    use windows::Win32::Foundation::{BOOL, HWND, LPARAM};
     use windows::Win32::UI::WindowsAndMessaging::*;
 
-    unsafe {
-        if GetParent(our_hwnd).unwrap_or_default() == detection.target_parent {
-            return;
+    struct FixData {
+        w: i32,
+        h: i32,
+    }
+    let fd = FixData { w, h };
+    unsafe extern "system" fn enum_fix_children(child: HWND, lp: LPARAM) -> BOOL {
+        if lp.0 == 0 {
+            return BOOL(0);
         }
+        let d = &*(lp.0 as *const FixData);
+        let mut st = GetWindowLongW(child, GWL_STYLE) as u32;
+        st &= !(WS_BORDER.0 | WS_THICKFRAME.0 | WS_DLGFRAME.0 | WS_CAPTION.0);
+        let _ = SetWindowLongW(child, GWL_STYLE, st as i32);
 
-        // 1. Strip ALL frame / border styles
-        let mut style = GetWindowLongW(our_hwnd, GWL_STYLE) as u32;
-        style &= !(WS_THICKFRAME.0
-            | WS_CAPTION.0
-            | WS_SYSMENU.0
-            | WS_MAXIMIZEBOX.0
-            | WS_MINIMIZEBOX.0
-            | WS_POPUP.0
-            | WS_BORDER.0
-            | WS_DLGFRAME.0);
-        style |= WS_CHILD.0 | WS_VISIBLE.0;
-        let _ = SetWindowLongW(our_hwnd, GWL_STYLE, style as i32);
+        let mut ex = GetWindowLongW(child, GWL_EXSTYLE) as u32;
+        ex &=
+            !(WS_EX_CLIENTEDGE.0 | WS_EX_WINDOWEDGE.0 | WS_EX_STATICEDGE.0 | WS_EX_DLGMODALFRAME.0);
+        let _ = SetWindowLongW(child, GWL_EXSTYLE, ex as i32);
 
-        let mut ex_style = GetWindowLongW(our_hwnd, GWL_EXSTYLE) as u32;
-        ex_style &= !(WS_EX_LAYERED.0
-            | WS_EX_NOACTIVATE.0
-            | WS_EX_CLIENTEDGE.0
-            | WS_EX_WINDOWEDGE.0
-            | WS_EX_DLGMODALFRAME.0
-            | WS_EX_STATICEDGE.0);
-        let _ = SetWindowLongW(our_hwnd, GWL_EXSTYLE, ex_style as i32);
+        SetClassLongPtrW(child, GCLP_HBRBACKGROUND, background_brush());
 
-        // 2. WM_NCCALCSIZE subclass → zero non-client area
-        let _ = windows::Win32::UI::Shell::SetWindowSubclass(
-            our_hwnd,
-            Some(nccalc_subclass_proc),
-            NCCALC_SUBCLASS_ID,
+        let _ = SetWindowPos(
+            child,
+            HWND::default(),
             0,
+            0,
+            d.w,
+            d.h,
+            SWP_NOZORDER | SWP_NOACTIVATE | SWP_FRAMECHANGED,
         );
-
-        // 3. Kill DWM border rendering
-        use windows::Win32::Graphics::Dwm::*;
-        let color_none: u32 = 0xFFFFFFFE; // DWMWA_COLOR_NONE
-        let no_round: i32 = 1; // DWMWCP_DONOTROUND
-        let _ = DwmSetWindowAttribute(
-            our_hwnd,
-            DWMWA_BORDER_COLOR,
-            &color_none as *const _ as *const _,
-            std::mem::size_of::<u32>() as u32,
-        );
-        let _ = DwmSetWindowAttribute(
+        BOOL(1)
+    }
+    unsafe {
+        let _ = EnumChildWindows(
             our_hwnd,
-            DWMWA_WINDOW_CORNER_PREFERENCE,
-            &no_round as *const _ as *const _,
-            std::mem::size_of::<i32>() as u32,
+            Some(enum_fix_children),
+            LPARAM(&fd as *const _ as isize),
         );
+    }
+    apply_window_region(our_hwnd);
+    // After all child fixes, so the controller doesn't see intermediate sizes
+    post_controller_bounds(w, h);
+}
 
-        // 4. Black background brush
-        use windows::Win32::Graphics::Gdi::{GetStockObject, BLACK_BRUSH};
-        SetClassLongPtrW(
-            our_hwnd,
-            GCLP_HBRBACKGROUND,
-            GetStockObject(BLACK_BRUSH).0 as isize,
-        );
+/// Spanning several monitors with the taskbar respected, the window covers
+/// the bounding box of their work areas, which overlaps the taskbar of any
+/// monitor whose work area is smaller. Clip it to the work areas themselves;
+/// in every other layout the region is cleared.
+#[cfg(target_os = "windows")]
+fn apply_window_region(our_hwnd: windows::Win32::Foundation::HWND) {
+    use windows::Win32::Graphics::Gdi::{
+        CombineRgn, CreateRectRgn, DeleteObject, SetWindowRgn, HRGN, RGN_OR,
+    };
 
-        // 5. Reparent into WorkerW (SW_SHOWNA preserves Z-order)
-        let _ = ShowWindow(detection.target_parent, SW_SHOWNA);
-        let _ = SetParent(our_hwnd, detection.target_parent);
+    let rects = layout_rects(&enumerate_monitors());
+    let spanning = rects
+        .get(WALLPAPER_MONITOR.load(Ordering::SeqCst) as usize)
+        .is_none();
+    unsafe {
+        if !(RESPECT_TASKBAR.load(Ordering::SeqCst) && spanning && rects.len() > 1) {
+            let _ = SetWindowRgn(our_hwnd, HRGN::default(), true);
+            return;
+        }
+        // Window coordinates: the render bounds' top-left is (0, 0)
+        let origin = MonitorBounds::union(&rects);
+        let region = CreateRectRgn(0, 0, 0, 0);
+        for r in &rects {
+            let (left, top) = (r.left - origin.left, r.top - origin.top);
+            let part = CreateRectRgn(left, top, left + r.width, top + r.height);
+            let _ = CombineRgn(region, region, part, RGN_OR);
+            let _ = DeleteObject(part);
+        }
+        // On success the system owns the region
+        if SetWindowRgn(our_hwnd, region, true) == 0 {
+            let _ = DeleteObject(region);
+        }
+    }
+}
 
-        // 6. Size to full monitor + force frame recalc
-        let _ = SetWindowPos(
-            our_hwnd,
-            HWND::default(),
-            0,
-            0,
-            detection.v_width,
-            detection.v_height,
-            SWP_FRAMECHANGED | SWP_SHOWWINDOW | SWP_NOZORDER,
-        );
-        let _ = ShowWindow(our_hwnd, SW_SHOW);
+/// Resize the WebView2 controller on the dispatch thread, which owns it.
+#[cfg(target_os = "windows")]
+fn post_controller_bounds(width: i32, height: i32) {
+    use windows::Win32::Foundation::{HWND, LPARAM, WPARAM};
+    use windows::Win32::UI::WindowsAndMessaging::PostMessageW;
 
-        // 7. Ensure WorkerW is BEHIND the icon layer so WindowFromPoint
-        //    returns SysListView32, enabling fully native icon interactions
-        //    (drag & drop, double-click, context menus, selection rectangle).
-        if !detection.zorder_anchor.is_invalid()
-            && detection.zorder_anchor != detection.target_parent
-        {
-            let _ = SetWindowPos(
-                detection.target_parent,
-                detection.zorder_anchor,
-                0,
-                0,
-                0,
-                0,
-                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+    let dh = mouse_hook::get_dispatch_hwnd();
+    if dh != 0 {
+        unsafe {
+            let _ = PostMessageW(
+                HWND(dh as *mut _),
+                mouse_hook::WM_MWP_SETBOUNDS_PUB,
+                WPARAM(width as usize),
+                LPARAM(height as isize),
             );
         }
+    }
+}
 
-        info!(
-            "[apply_injection] Done. Parent=0x{:X}, Size={}x{}",
-            detection.target_parent.0 as isize, detection.v_width, detection.v_height
-        );
+/// Tell the frontend about lock/unlock so it can pause heavy animations.
+#[cfg(target_os = "windows")]
+fn emit_session_state(active: bool) {
+    use crate::events::{AppEvent, EmitAppEvent};
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit_app_event(&AppEvent::SessionStateChanged { active });
     }
 }
 
-// ==============================================================================
-// Windows: Initialization
-// ==============================================================================
+/// Called from the dispatch window on WM_DISPLAYCHANGE: notify the frontend and
+/// re-detect so the wallpaper follows the new virtual desktop bounds.
+#[cfg(target_os = "windows")]
+fn on_display_change() {
+    if DISPLAY_CHANGE_PENDING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    // Off the dispatch thread: detect_desktop blocks on SendMessageTimeout + sleep
+    std::thread::spawn(|| {
+        use crate::events::{AppEvent, EmitAppEvent};
+
+        // Let the display settle; dock/undock fires several changes in a row
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        DISPLAY_CHANGE_PENDING.store(false, Ordering::SeqCst);
+
+        let monitors = enumerate_monitors();
+        refresh_virtual_bounds(&monitors);
+        info!(
+            "[window_layer] Display change: {} monitor(s)",
+            monitors.len()
+        );
+        if let Some(app) = APP_HANDLE.get() {
+            let _ = app.emit_app_event(&AppEvent::MonitorsChanged {
+                count: monitors.len() as u32,
+                monitors,
+            });
+            crate::tray::rebuild_monitor_menu(app);
+            let _ = app.emit_app_event(&AppEvent::MonitorDpiChanged {
+                monitors: enumerate_monitor_dpi(),
+            });
+        }
+
+        if let Err(e) = relayout() {
+            error!(
+                "[window_layer] Re-detection after display change failed: {}",
+                e
+            );
+            record_injection_failure(&e);
+        }
+    });
+}
 
+/// Fallback when no WorkerW exists (some VMs and locked-down shells): a
+/// bottom-most, non-activating top-level window over the virtual desktop.
+/// Imperfect, as it can cover icons, but visible rather than lost behind Progman.
 #[cfg(target_os = "windows")]
-fn ensure_in_worker_w(window: &tauri::WebviewWindow) -> crate::error::AppResult<()> {
+fn place_bottom_fallback(our_hwnd: windows::Win32::Foundation::HWND) {
     use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::*;
 
-    let _ = window.set_ignore_cursor_events(false);
-    let our_hwnd_raw = window.hwnd()?;
-    let our_hwnd = HWND(our_hwnd_raw.0 as *mut _);
+    let bounds = MonitorBounds::render(&enumerate_monitors());
+    unsafe {
+        if !GetParent(our_hwnd).unwrap_or_default().is_invalid() {
+            let _ = SetParent(our_hwnd, HWND::default());
+        }
+        let mut style = GetWindowLongW(our_hwnd, GWL_STYLE) as u32;
+        style &= !(WS_CHILD.0 | WS_CAPTION.0 | WS_THICKFRAME.0);
+        style |= WS_POPUP.0 | WS_VISIBLE.0;
+        let _ = SetWindowLongW(our_hwnd, GWL_STYLE, style as i32);
+        let ex = GetWindowLongW(our_hwnd, GWL_EXSTYLE) as u32 | WS_EX_NOACTIVATE.0;
+        let _ = SetWindowLongW(our_hwnd, GWL_EXSTYLE, ex as i32);
+        let _ = SetWindowPos(
+            our_hwnd,
+            HWND_BOTTOM,
+            bounds.left,
+            bounds.top,
+            bounds.right - bounds.left,
+            bounds.bottom - bounds.top,
+            SWP_NOACTIVATE | SWP_FRAMECHANGED | SWP_SHOWWINDOW,
+        );
+    }
+    log::warn!("[window_layer] WorkerW not found, using bottom-most window fallback");
+}
 
-    let detection = detect_desktop()?;
+/// Pull the window out of WorkerW as a borderless top-level window covering
+/// the virtual desktop.
+#[cfg(target_os = "windows")]
+fn detach_from_desktop(our_hwnd: isize, topmost: bool) {
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::UI::WindowsAndMessaging::*;
 
-    mouse_hook::set_webview_hwnd(our_hwnd.0 as isize);
-    mouse_hook::set_target_parent_hwnd(detection.target_parent.0 as isize);
-    mouse_hook::set_progman_hwnd(detection.progman.0 as isize);
-    mouse_hook::set_explorer_pid(detection.explorer_pid);
-    if !detection.syslistview.is_invalid() {
-        mouse_hook::set_syslistview_hwnd(detection.syslistview.0 as isize);
+    let hwnd = HWND(our_hwnd as *mut _);
+    let bounds = MonitorBounds::render(&enumerate_monitors());
+    remove_nccalc_subclass(hwnd);
+    unsafe {
+        let _ = SetParent(hwnd, HWND::default());
+        let mut style = GetWindowLongW(hwnd, GWL_STYLE) as u32;
+        style &= !WS_CHILD.0;
+        style |= WS_POPUP.0 | WS_VISIBLE.0;
+        let _ = SetWindowLongW(hwnd, GWL_STYLE, style as i32);
+        let _ = SetWindowPos(
+            hwnd,
+            if topmost {
+                HWND_TOPMOST
+            } else {
+                HWND_NOTOPMOST
+            },
+            bounds.left,
+            bounds.top,
+            bounds.right - bounds.left,
+            bounds.bottom - bounds.top,
+            SWP_FRAMECHANGED | SWP_SHOWWINDOW,
+        );
     }
+    LAYER_INJECTED.store(false, Ordering::SeqCst);
+    info!("[window_layer] Detached from desktop (topmost={})", topmost);
+}
 
-    apply_injection(our_hwnd, &detection);
-    mouse_hook::init_dispatch_window();
+/// Add or remove the click-through overlay styles. The hook never forwards in
+/// overlay mode: `WindowFromPoint` skips transparent windows.
+#[cfg(target_os = "windows")]
+fn set_overlay_styles(our_hwnd: isize, enabled: bool) {
+    use tauri::Manager;
+    use windows::Win32::Foundation::{COLORREF, HWND};
+    use windows::Win32::UI::WindowsAndMessaging::*;
 
-    let (w, h) = (detection.v_width, detection.v_height);
-    let our_hwnd_isize = our_hwnd.0 as isize;
+    let hwnd = HWND(our_hwnd as *mut _);
+    if let Some(w) = APP_HANDLE.get().and_then(|a| a.get_webview_window("main")) {
+        let _ = w.set_ignore_cursor_events(enabled);
+    }
+    unsafe {
+        let overlay = WS_EX_TOPMOST.0 | WS_EX_TRANSPARENT.0 | WS_EX_LAYERED.0;
+        let mut ex = GetWindowLongW(hwnd, GWL_EXSTYLE) as u32;
+        if enabled {
+            ex |= overlay;
+        } else {
+            ex &= !overlay;
+        }
+        let _ = SetWindowLongW(hwnd, GWL_EXSTYLE, ex as i32);
+        if enabled {
+            // A layered window stays invisible until it has attributes
+            let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), 255, LWA_ALPHA);
+        }
+    }
+    info!(
+        "[window_layer] Overlay styles {}",
+        if enabled { "on" } else { "off" }
+    );
+}
 
+/// Event-driven explorer.exe restart detection.
+/// Blocks on the explorer process handle instead of polling, then waits for the
+/// new Progman window to appear and re-injects within ~1-2s of the shell coming back.
+#[cfg(target_os = "windows")]
+fn start_explorer_watcher(our_hwnd: isize, initial_pid: u32) {
     std::thread::spawn(move || {
-        use windows::Win32::Foundation::{BOOL, HWND, LPARAM, WPARAM};
-        use windows::Win32::UI::WindowsAndMessaging::*;
+        use std::time::Duration;
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::System::Threading::{
+            OpenProcess, WaitForSingleObject, INFINITE, PROCESS_SYNCHRONIZE,
+        };
+        use windows::Win32::UI::WindowsAndMessaging::{FindWindowW, GetWindowThreadProcessId};
 
-        let mut found = false;
-        for _ in 1..=100 {
-            let ptr = wry::get_last_composition_controller_ptr();
-            if ptr != 0 {
-                mouse_hook::set_comp_controller_ptr(ptr);
+        const PROGMAN_POLL: Duration = Duration::from_millis(250);
+        // Fallback when the process handle can't be opened
+        const OWNER_POLL: Duration = Duration::from_secs(2);
+        // Explorer recreates Progman before the shell view is populated
+        const SHELL_SETTLE: Duration = Duration::from_millis(500);
 
-                unsafe {
-                    let wv_h = HWND(our_hwnd_isize as *mut _);
-                    let _ = SetWindowPos(
-                        wv_h,
-                        HWND::default(),
-                        0,
-                        0,
-                        w,
-                        h,
-                        SWP_NOZORDER | SWP_SHOWWINDOW | SWP_FRAMECHANGED,
-                    );
+        // Owner of the current Progman; 0 while there is none
+        let progman_pid = || unsafe {
+            let mut p: u32 = 0;
+            if let Ok(progman) = FindWindowW(windows::core::w!("Progman"), None) {
+                GetWindowThreadProcessId(progman, Some(&mut p));
+            }
+            p
+        };
 
-                    // Fix all child windows: strip borders, set black brush, force full size
-                    struct FixData {
-                        w: i32,
-                        h: i32,
-                    }
-                    let fd = FixData { w, h };
-                    unsafe extern "system" fn enum_fix_children(child: HWND, lp: LPARAM) -> BOOL {
-                        if lp.0 == 0 {
-                            return BOOL(0);
+        let mut pid = initial_pid;
+        loop {
+            unsafe {
+                // 1. Wait for the current explorer.exe to exit
+                if pid != 0 {
+                    match OpenProcess(PROCESS_SYNCHRONIZE, false, pid) {
+                        Ok(proc) => {
+                            let _ = WaitForSingleObject(proc, INFINITE);
+                            let _ = CloseHandle(proc);
+                            info!("[explorer_watcher] explorer.exe (pid={}) exited", pid);
+                        }
+                        Err(e) => {
+                            // Already gone or not ours to open: poll until
+                            // Progman no longer belongs to it
+                            error!("[explorer_watcher] OpenProcess(pid={}) failed: {}", pid, e);
+                            while progman_pid() == pid {
+                                std::thread::sleep(OWNER_POLL);
+                            }
                         }
-                        let d = &*(lp.0 as *const FixData);
-                        let mut st = GetWindowLongW(child, GWL_STYLE) as u32;
-                        st &= !(WS_BORDER.0 | WS_THICKFRAME.0 | WS_DLGFRAME.0 | WS_CAPTION.0);
-                        let _ = SetWindowLongW(child, GWL_STYLE, st as i32);
-
-                        let mut ex = GetWindowLongW(child, GWL_EXSTYLE) as u32;
-                        ex &= !(WS_EX_CLIENTEDGE.0
-                            | WS_EX_WINDOWEDGE.0
-                            | WS_EX_STATICEDGE.0
-                            | WS_EX_DLGMODALFRAME.0);
-                        let _ = SetWindowLongW(child, GWL_EXSTYLE, ex as i32);
-
-                        use windows::Win32::Graphics::Gdi::{GetStockObject, BLACK_BRUSH};
-                        SetClassLongPtrW(
-                            child,
-                            GCLP_HBRBACKGROUND,
-                            GetStockObject(BLACK_BRUSH).0 as isize,
-                        );
-
-                        let _ = SetWindowPos(
-                            child,
-                            HWND::default(),
-                            0,
-                            0,
-                            d.w,
-                            d.h,
-                            SWP_NOZORDER | SWP_NOACTIVATE | SWP_FRAMECHANGED,
-                        );
-                        BOOL(1)
                     }
-                    let _ = EnumChildWindows(
-                        wv_h,
-                        Some(enum_fix_children),
-                        LPARAM(&fd as *const _ as isize),
-                    );
+                }
 
-                    // Set WebView2 bounds once after all child fixes
-                    let dh = mouse_hook::get_dispatch_hwnd();
-                    if dh != 0 {
-                        let _ = PostMessageW(
-                            HWND(dh as *mut _),
-                            mouse_hook::WM_MWP_SETBOUNDS_PUB,
-                            WPARAM(w as usize),
-                            LPARAM(h as isize),
-                        );
+                // 2. Wait for a new Progman owned by a different explorer instance
+                let new_pid = loop {
+                    let p = progman_pid();
+                    if p != 0 && p != pid {
+                        break p;
                     }
+                    std::thread::sleep(PROGMAN_POLL);
+                };
+
+                // 3. Re-inject into the new shell
+                std::thread::sleep(SHELL_SETTLE);
+                info!(
+                    "[explorer_watcher] New explorer.exe (pid={}), re-injecting...",
+                    new_pid
+                );
+                if let Err(e) = reinject(our_hwnd) {
+                    error!("[explorer_watcher] Re-injection failed: {}", e);
+                    record_injection_failure(&e);
+                }
+                pid = new_pid;
+            }
+        }
+    });
+}
+
+// ==============================================================================
+// Windows: Foreground Pause Watcher
+// ==============================================================================
+
+/// Polls the foreground window: a fullscreen window or an app from the pause
+/// list hides the wallpaper until it leaves the foreground.
+#[cfg(target_os = "windows")]
+fn start_foreground_watcher() {
+    static RUNNING: AtomicBool = AtomicBool::new(false);
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+        // Process name lookup is the expensive part; only redo it when the
+        // foreground window changes.
+        let mut cached: (isize, Option<String>) = (0, None);
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            let fg = unsafe { GetForegroundWindow() };
+            if fg.0 as isize != cached.0 {
+                cached = (fg.0 as isize, unsafe { foreground_exe_name(fg) });
+            }
+            let listed = cached
+                .1
+                .as_ref()
+                .is_some_and(|exe| PAUSE_APPS.lock().unwrap().contains(exe));
+            let paused = listed || unsafe { is_fullscreen(fg) };
+            if FOREGROUND_PAUSED.swap(paused, Ordering::SeqCst) != paused {
+                info!(
+                    "[foreground] Wallpaper {} ({})",
+                    if paused { "paused" } else { "resumed" },
+                    cached.1.as_deref().unwrap_or("?")
+                );
+                if let Some(app) = APP_HANDLE.get() {
+                    crate::tray::emit_visibility(app);
                 }
-                found = true;
-                break;
             }
-            std::thread::sleep(std::time::Duration::from_millis(10));
         }
-        if !found {
-            error!("[window_layer] Timed out waiting for composition controller (1s)");
+    });
+}
+
+/// Lowercased image file name of the process owning `hwnd`, ignoring our own.
+#[cfg(target_os = "windows")]
+unsafe fn foreground_exe_name(hwnd: windows::Win32::Foundation::HWND) -> Option<String> {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+    if hwnd.is_invalid() {
+        return None;
+    }
+    let mut pid = 0u32;
+    GetWindowThreadProcessId(hwnd, Some(&mut pid));
+    if pid == 0 || pid == std::process::id() {
+        return None;
+    }
+    let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+    let mut buf = [0u16; 1024];
+    let mut len = buf.len() as u32;
+    let ok = QueryFullProcessImageNameW(
+        process,
+        PROCESS_NAME_WIN32,
+        windows::core::PWSTR(buf.as_mut_ptr()),
+        &mut len,
+    )
+    .is_ok();
+    let _ = CloseHandle(process);
+    if !ok {
+        return None;
+    }
+    let path = String::from_utf16_lossy(&buf[..len as usize]);
+    path.rsplit('\\').next().map(|n| n.to_lowercase())
+}
+
+/// A window (other than ours or the desktop/shell) covering its whole monitor.
+#[cfg(target_os = "windows")]
+unsafe fn is_fullscreen(hwnd: windows::Win32::Foundation::HWND) -> bool {
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::Graphics::Gdi::{
+        GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONULL,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowRect, GetWindowThreadProcessId, IsWindowVisible,
+    };
+
+    if hwnd.is_invalid() || !IsWindowVisible(hwnd).as_bool() {
+        return false;
+    }
+    // A detached (normal/topmost) wallpaper covers the screen itself
+    let mut pid = 0u32;
+    GetWindowThreadProcessId(hwnd, Some(&mut pid));
+    if pid == std::process::id() {
+        return false;
+    }
+    if ["Progman", "WorkerW", "Shell_TrayWnd"]
+        .iter()
+        .any(|c| is_class_name(hwnd, c))
+    {
+        return false;
+    }
+    let mut rc = RECT::default();
+    if GetWindowRect(hwnd, &mut rc).is_err() {
+        return false;
+    }
+    let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL);
+    if monitor.is_invalid() {
+        return false;
+    }
+    let mut mi = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if !GetMonitorInfoW(monitor, &mut mi).as_bool() {
+        return false;
+    }
+    let m = mi.rcMonitor;
+    rc.left <= m.left && rc.top <= m.top && rc.right >= m.right && rc.bottom >= m.bottom
+}
+
+// ==============================================================================
+// Windows: File Drop Target
+// ==============================================================================
+
+/// OLE drop target on the window that hosts the WebView2 composition visual,
+/// so widgets receive dropped files as `files-dropped`. In composition mode
+/// WebView2 has no window of its own to take drops, so the host must.
+///
+/// Precedence follows what is under the cursor: in the Desktop layer mode the
+/// wallpaper sits below SHELLDLL_DefView, so Explorer keeps every drop (on an
+/// icon or on empty desktop) and this target never sees one. In the detached
+/// Normal/Topmost modes nothing covers the wallpaper and drops land here.
+/// Overlay is click-through, so drops pass to the window beneath.
+#[cfg(target_os = "windows")]
+mod drop_target {
+    use crate::events::{AppEvent, EmitAppEvent};
+    use std::cell::Cell;
+    use windows::core::implement;
+    use windows::Win32::Foundation::{HWND, POINTL};
+    use windows::Win32::System::Com::{IDataObject, DVASPECT_CONTENT, FORMATETC, TYMED_HGLOBAL};
+    use windows::Win32::System::Ole::{
+        IDropTarget, IDropTarget_Impl, RegisterDragDrop, ReleaseStgMedium, RevokeDragDrop,
+        CF_HDROP, DROPEFFECT, DROPEFFECT_COPY, DROPEFFECT_NONE,
+    };
+    use windows::Win32::System::SystemServices::MODIFIERKEYS_FLAGS;
+    use windows::Win32::UI::Shell::{DragQueryFileW, HDROP};
+
+    #[implement(IDropTarget)]
+    struct WallpaperDropTarget {
+        /// Whether the current drag carries files at all.
+        has_files: Cell<bool>,
+    }
+
+    impl WallpaperDropTarget {
+        fn effect(&self) -> DROPEFFECT {
+            if self.has_files.get() {
+                DROPEFFECT_COPY
+            } else {
+                DROPEFFECT_NONE
+            }
+        }
+    }
+
+    fn hdrop_format() -> FORMATETC {
+        FORMATETC {
+            cfFormat: CF_HDROP.0,
+            ptd: std::ptr::null_mut(),
+            dwAspect: DVASPECT_CONTENT.0,
+            lindex: -1,
+            tymed: TYMED_HGLOBAL.0 as u32,
+        }
+    }
+
+    /// File paths from a CF_HDROP data object, or None if it carries no files.
+    unsafe fn dropped_paths(data: &IDataObject) -> Option<Vec<String>> {
+        let mut medium = data.GetData(&hdrop_format()).ok()?;
+        let hdrop = HDROP(medium.u.hGlobal.0 as _);
+        let count = DragQueryFileW(hdrop, u32::MAX, None);
+        let paths = (0..count)
+            .map(|i| {
+                let len = DragQueryFileW(hdrop, i, None) as usize;
+                let mut buf = vec![0u16; len + 1];
+                DragQueryFileW(hdrop, i, Some(&mut buf));
+                String::from_utf16_lossy(&buf[..len])
+            })
+            .collect();
+        ReleaseStgMedium(&mut medium);
+        Some(paths)
+    }
+
+    #[allow(non_snake_case)]
+    impl IDropTarget_Impl for WallpaperDropTarget_Impl {
+        fn DragEnter(
+            &self,
+            pdataobj: Option<&IDataObject>,
+            _grfkeystate: MODIFIERKEYS_FLAGS,
+            _pt: &POINTL,
+            pdweffect: *mut DROPEFFECT,
+        ) -> windows::core::Result<()> {
+            let has_files =
+                pdataobj.is_some_and(|d| unsafe { d.QueryGetData(&hdrop_format()).is_ok() });
+            self.has_files.set(has_files);
+            unsafe { *pdweffect = self.effect() };
+            Ok(())
+        }
+
+        fn DragOver(
+            &self,
+            _grfkeystate: MODIFIERKEYS_FLAGS,
+            _pt: &POINTL,
+            pdweffect: *mut DROPEFFECT,
+        ) -> windows::core::Result<()> {
+            unsafe { *pdweffect = self.effect() };
+            Ok(())
         }
-    });
 
-    mouse_hook::start_hook_thread();
+        fn DragLeave(&self) -> windows::core::Result<()> {
+            self.has_files.set(false);
+            Ok(())
+        }
 
-    // Zombie window watchdog: re-detects desktop if parent HWND becomes stale
-    WATCHDOG_PARENT.store(detection.target_parent.0 as isize, Ordering::SeqCst);
-    let watchdog_our = our_hwnd.0 as isize;
-    std::thread::spawn(move || {
-        use std::time::Duration;
-        use windows::Win32::UI::WindowsAndMessaging::IsWindow;
-        loop {
-            std::thread::sleep(Duration::from_secs(30));
-            let parent_raw = WATCHDOG_PARENT.load(Ordering::SeqCst);
-            if parent_raw == 0 {
-                continue;
+        fn Drop(
+            &self,
+            pdataobj: Option<&IDataObject>,
+            _grfkeystate: MODIFIERKEYS_FLAGS,
+            _pt: &POINTL,
+            pdweffect: *mut DROPEFFECT,
+        ) -> windows::core::Result<()> {
+            let effect = self.effect();
+            self.has_files.set(false);
+            unsafe { *pdweffect = effect };
+            if effect == DROPEFFECT_NONE {
+                return Ok(());
             }
-            unsafe {
-                if !IsWindow(HWND(parent_raw as *mut _)).as_bool() {
-                    info!("[watchdog] Parent HWND stale, re-detecting desktop...");
-                    match detect_desktop() {
-                        Ok(d) => {
-                            mouse_hook::set_target_parent_hwnd(d.target_parent.0 as isize);
-                            mouse_hook::set_progman_hwnd(d.progman.0 as isize);
-                            mouse_hook::set_explorer_pid(d.explorer_pid);
-                            if !d.syslistview.is_invalid() {
-                                mouse_hook::set_syslistview_hwnd(d.syslistview.0 as isize);
-                            }
-                            apply_injection(HWND(watchdog_our as *mut _), &d);
-                            WATCHDOG_PARENT.store(d.target_parent.0 as isize, Ordering::SeqCst);
-                            info!("[watchdog] Re-injection done");
-                        }
-                        Err(e) => error!("[watchdog] Re-detection failed: {}", e),
-                    }
-                }
+            let Some(paths) = pdataobj.and_then(|d| unsafe { dropped_paths(d) }) else {
+                return Ok(());
+            };
+            log::info!("[drop_target] {} file(s) dropped on wallpaper", paths.len());
+            if let Some(app) = super::APP_HANDLE.get() {
+                let _ = app.emit_app_event(&AppEvent::FilesDropped { paths });
             }
+            Ok(())
         }
-    });
+    }
 
-    Ok(())
+    /// Register on the WebView2 host window. Must run on the window's (OLE)
+    /// thread; re-registering replaces the previous target.
+    pub fn register(hwnd: HWND) {
+        let target: IDropTarget = WallpaperDropTarget {
+            has_files: Cell::new(false),
+        }
+        .into();
+        unsafe {
+            let _ = RevokeDragDrop(hwnd);
+            if let Err(e) = RegisterDragDrop(hwnd, &target) {
+                log::warn!("[drop_target] RegisterDragDrop failed: {}", e);
+            }
+        }
+    }
+
+    pub fn revoke(hwnd: HWND) {
+        unsafe {
+            let _ = RevokeDragDrop(hwnd);
+        }
+    }
 }
 
 // ==============================================================================
@@ -596,19 +2809,21 @@ fn ensure_in_worker_w(window: &tauri::WebviewWindow) -> crate::error::AppResult<
 
 #[cfg(target_os = "windows")]
 pub mod mouse_hook {
-    use std::sync::atomic::{AtomicIsize, AtomicU32, Ordering};
+    use std::sync::atomic::{AtomicI64, AtomicIsize, AtomicU32, AtomicU64, Ordering};
     use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
     use windows::Win32::UI::WindowsAndMessaging::*;
 
     const MOUSE_MOVE: i32 = 0x0200;
     const MOUSE_LDOWN: i32 = 0x0201;
     const MOUSE_LUP: i32 = 0x0202;
+    const MOUSE_LDBLCLK: i32 = 0x0203;
     const MOUSE_RDOWN: i32 = 0x0204;
     const MOUSE_RUP: i32 = 0x0205;
     const MOUSE_MDOWN: i32 = 0x0207;
     const MOUSE_MUP: i32 = 0x0208;
     const MOUSE_WHEEL: i32 = 0x020A;
     const MOUSE_HWHEEL: i32 = 0x020E;
+    const MOUSE_LEAVE: i32 = 0x02A3;
     const MK_NONE: i32 = 0x0;
     const MK_LBUTTON: i32 = 0x0001;
     const MK_RBUTTON: i32 = 0x0002;
@@ -616,17 +2831,27 @@ pub mod mouse_hook {
 
     static WEBVIEW_HWND: AtomicIsize = AtomicIsize::new(0);
     static SYSLISTVIEW_HWND: AtomicIsize = AtomicIsize::new(0);
-    static TARGET_PARENT_HWND: AtomicIsize = AtomicIsize::new(0);
+    static TARGETS: std::sync::RwLock<Vec<HookTarget>> = std::sync::RwLock::new(Vec::new());
     static PROGMAN_HWND: AtomicIsize = AtomicIsize::new(0);
     static EXPLORER_PID: AtomicU32 = AtomicU32::new(0);
     static DESKTOP_CORE_HWND: AtomicIsize = AtomicIsize::new(0);
     static COMP_CONTROLLER_PTR: AtomicIsize = AtomicIsize::new(0);
     static DRAG_VK: AtomicIsize = AtomicIsize::new(0);
     static DISPATCH_HWND: AtomicIsize = AtomicIsize::new(0);
+    /// Thread running the hook's message loop; 0 when none.
+    static HOOK_THREAD_ID: AtomicU32 = AtomicU32::new(0);
     static CHROME_RWHH: AtomicIsize = AtomicIsize::new(0);
     static NATIVE_DRAG: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
     static INJECTING_FOR_DRAG: std::sync::atomic::AtomicBool =
         std::sync::atomic::AtomicBool::new(false);
+    /// False while the window is detached (normal/topmost layer mode).
+    static DESKTOP_MODE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(true);
+    /// True while nothing should be forwarded (wallpaper preview).
+    static FORWARDING_PAUSED: std::sync::atomic::AtomicBool =
+        std::sync::atomic::AtomicBool::new(false);
+    /// False while clicks must never reach SysListView32 (icon interaction off).
+    static ICON_INTERACTION: std::sync::atomic::AtomicBool =
+        std::sync::atomic::AtomicBool::new(true);
     #[allow(dead_code)]
     static THREADS_ATTACHED: std::sync::atomic::AtomicBool =
         std::sync::atomic::AtomicBool::new(false);
@@ -639,6 +2864,73 @@ pub mod mouse_hook {
 
     pub const WM_MWP_SETBOUNDS_PUB: u32 = 0x8000 + 43;
     const WM_MWP_MOUSE: u32 = 0x8000 + 42;
+    /// Asks the dispatch thread to arm the deferred-move flush timer.
+    const WM_MWP_ARM_FLUSH: u32 = 0x8000 + 44;
+    const FLUSH_TIMER_ID: usize = 1;
+    /// Asks the dispatch thread to mark a newly discovered render host.
+    const WM_MWP_MARK_RWHH: u32 = 0x8000 + 45;
+
+    /// Window props read by the leave-suppression hook DLL: `MWP_T` on the
+    /// render host, `MWP_T2` on the widgets nested under it, which newer
+    /// WebView2 builds route hover to. The DLL turns WM_MOUSELEAVE and
+    /// WM_NCMOUSELEAVE on marked windows into WM_NULL unless `MWP_E` (explicit
+    /// leave) is set.
+    const PROP_RENDER_HOST: windows::core::PCWSTR = windows::core::w!("MWP_T");
+    const PROP_RENDER_CHILD: windows::core::PCWSTR = windows::core::w!("MWP_T2");
+    /// Set on the render host while suppression is switched off; the DLL
+    /// passes both leave messages through untouched when it sees it.
+    const PROP_SUPPRESSION_OFF: windows::core::PCWSTR = windows::core::w!("MWP_OFF");
+    static LEAVE_SUPPRESSION: std::sync::atomic::AtomicBool =
+        std::sync::atomic::AtomicBool::new(true);
+    static MARKED_RWHH: AtomicIsize = AtomicIsize::new(0);
+    /// Leave-suppression hook DLL, loaded from the exe's directory only.
+    const LEAVE_HOOK_DLL: &str = "mwp_hook.dll";
+    static LEAVE_HOOK_MODULE: AtomicIsize = AtomicIsize::new(0);
+    static LEAVE_HOOK: AtomicIsize = AtomicIsize::new(0);
+
+    /// Screen rects (left, top, right, bottom) where input passes through
+    /// untouched. Guarded by a seqlock so the hook reads without locking:
+    /// `EXCLUSION_SEQ` is odd while a write is in progress.
+    static EXCLUSION_RECTS: [[std::sync::atomic::AtomicI32; 4];
+        crate::commands_core::MAX_EXCLUSION_RECTS] =
+        [const { [const { std::sync::atomic::AtomicI32::new(0) }; 4] };
+            crate::commands_core::MAX_EXCLUSION_RECTS];
+    static EXCLUSION_COUNT: AtomicU32 = AtomicU32::new(0);
+    static EXCLUSION_SEQ: AtomicU32 = AtomicU32::new(0);
+    /// Serializes writers; the hook never takes it.
+    static EXCLUSION_WRITE: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    /// `hook_proc` timing for `get_hook_stats`. Only the hook thread writes
+    /// the window counters, so plain loads and stores suffice.
+    static TIMING_ENABLED: std::sync::atomic::AtomicBool =
+        std::sync::atomic::AtomicBool::new(false);
+    static QPC_FREQUENCY: AtomicU64 = AtomicU64::new(1);
+    /// QPC tick the current window opened at; 0 opens one on the next call.
+    static TIMING_WINDOW_START: AtomicI64 = AtomicI64::new(0);
+    static TIMING_CALLS: AtomicU32 = AtomicU32::new(0);
+    static TIMING_TOTAL_TICKS: AtomicU64 = AtomicU64::new(0);
+    static TIMING_MAX_TICKS: AtomicU64 = AtomicU64::new(0);
+    /// The last completed window, as published to `timing_stats`.
+    static STATS_CALLS: AtomicU32 = AtomicU32::new(0);
+    static STATS_TOTAL_TICKS: AtomicU64 = AtomicU64::new(0);
+    static STATS_MAX_TICKS: AtomicU64 = AtomicU64::new(0);
+
+    /// Minimum gap between forwarded moves in ms; 0 disables throttling.
+    static MOVE_INTERVAL_MS: AtomicU32 = AtomicU32::new(0);
+    static LAST_MOVE_TIME: AtomicU32 = AtomicU32::new(0);
+    /// Latest throttled move, packed by `pack_move`; 0 when none is waiting.
+    static PENDING_MOVE: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+    /// Where input over one monitor is forwarded: the WebView covering it,
+    /// that WebView's desktop parent and the monitor rect in screen coordinates.
+    #[derive(Debug, Clone, Copy)]
+    pub struct HookTarget {
+        pub webview: isize,
+        pub parent: isize,
+        /// Composition controller for `webview`; 0 uses the shared one.
+        pub controller: isize,
+        pub rect: windows::Win32::Foundation::RECT,
+    }
 
     pub fn set_webview_hwnd(h: isize) {
         WEBVIEW_HWND.store(h, Ordering::SeqCst);
@@ -646,52 +2938,268 @@ pub mod mouse_hook {
     pub fn set_syslistview_hwnd(h: isize) {
         SYSLISTVIEW_HWND.store(h, Ordering::SeqCst);
     }
-    pub fn set_target_parent_hwnd(h: isize) {
-        TARGET_PARENT_HWND.store(h, Ordering::SeqCst);
+    /// Replace the per-monitor forwarding targets.
+    pub fn set_targets(targets: Vec<HookTarget>) {
+        *TARGETS.write().unwrap() = targets;
+    }
+    /// Replace the exclusion rects. Callers validate the count.
+    pub fn set_exclusion_rects(rects: &[crate::commands_core::ScreenRect]) {
+        use std::sync::atomic::fence;
+
+        let _guard = EXCLUSION_WRITE.lock().unwrap();
+        let seq = EXCLUSION_SEQ.load(Ordering::Relaxed);
+        EXCLUSION_SEQ.store(seq.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+        for (slot, r) in EXCLUSION_RECTS.iter().zip(rects) {
+            let edges = [r.left, r.top, r.left + r.width, r.top + r.height];
+            for (a, v) in slot.iter().zip(edges) {
+                a.store(v, Ordering::Relaxed);
+            }
+        }
+        EXCLUSION_COUNT.store(rects.len() as u32, Ordering::Relaxed);
+        EXCLUSION_SEQ.store(seq.wrapping_add(2), Ordering::Release);
     }
     pub fn set_progman_hwnd(h: isize) {
         PROGMAN_HWND.store(h, Ordering::SeqCst);
     }
+    pub fn get_progman_hwnd() -> isize {
+        PROGMAN_HWND.load(Ordering::SeqCst)
+    }
+    pub fn set_desktop_mode(enabled: bool) {
+        DESKTOP_MODE.store(enabled, Ordering::SeqCst);
+    }
+    pub fn set_forwarding_paused(paused: bool) {
+        FORWARDING_PAUSED.store(paused, Ordering::SeqCst);
+    }
     pub fn set_explorer_pid(pid: u32) {
         EXPLORER_PID.store(pid, Ordering::SeqCst);
     }
+    pub fn get_webview_hwnd() -> isize {
+        WEBVIEW_HWND.load(Ordering::SeqCst)
+    }
+    pub fn clear_desktop_core_hwnd() {
+        DESKTOP_CORE_HWND.store(0, Ordering::SeqCst);
+    }
     pub fn get_syslistview_hwnd() -> isize {
         SYSLISTVIEW_HWND.load(Ordering::SeqCst)
     }
     pub fn set_comp_controller_ptr(p: isize) {
         COMP_CONTROLLER_PTR.store(p, Ordering::SeqCst);
     }
+    /// Re-read state that a navigation can invalidate: the composition
+    /// controller pointer and the (possibly recreated) renderer HWND.
+    pub fn refresh_webview_handles() {
+        let ptr = wry::get_last_composition_controller_ptr();
+        if ptr != 0 && ptr != COMP_CONTROLLER_PTR.swap(ptr, Ordering::SeqCst) {
+            log::info!("[hook] Composition controller changed after reload");
+        }
+        // Re-discovered lazily by is_over_desktop
+        CHROME_RWHH.store(0, Ordering::SeqCst);
+    }
     pub fn get_comp_controller_ptr() -> isize {
         COMP_CONTROLLER_PTR.load(Ordering::SeqCst)
     }
     pub fn get_dispatch_hwnd() -> isize {
         DISPATCH_HWND.load(Ordering::SeqCst)
     }
+    pub fn set_timing_enabled(enabled: bool) {
+        use windows::Win32::System::Performance::QueryPerformanceFrequency;
 
+        if enabled {
+            let mut freq = 0i64;
+            let _ = unsafe { QueryPerformanceFrequency(&mut freq) };
+            QPC_FREQUENCY.store(freq.max(1) as u64, Ordering::SeqCst);
+            TIMING_WINDOW_START.store(0, Ordering::SeqCst);
+            TIMING_CALLS.store(0, Ordering::SeqCst);
+            TIMING_TOTAL_TICKS.store(0, Ordering::SeqCst);
+            TIMING_MAX_TICKS.store(0, Ordering::SeqCst);
+            STATS_CALLS.store(0, Ordering::SeqCst);
+            STATS_TOTAL_TICKS.store(0, Ordering::SeqCst);
+            STATS_MAX_TICKS.store(0, Ordering::SeqCst);
+        }
+        TIMING_ENABLED.store(enabled, Ordering::SeqCst);
+    }
+    pub fn timing_stats() -> super::HookStats {
+        let freq = QPC_FREQUENCY.load(Ordering::Relaxed) as f64;
+        let calls = STATS_CALLS.load(Ordering::Relaxed);
+        let micros = |ticks: u64| ticks as f64 * 1_000_000.0 / freq;
+        super::HookStats {
+            enabled: TIMING_ENABLED.load(Ordering::Relaxed),
+            calls,
+            avg_micros: micros(STATS_TOTAL_TICKS.load(Ordering::Relaxed)) / calls.max(1) as f64,
+            max_micros: micros(STATS_MAX_TICKS.load(Ordering::Relaxed)),
+        }
+    }
     #[inline]
-    unsafe fn post_mouse(kind: i32, vk: i32, data: u32, x: i32, y: i32) {
-        // Encoding packs 3 fields into a single usize via bit shifts.
-        // The <<32 shift requires a 64-bit pointer width; on 32-bit it would silently lose data.
-        const _: () = assert!(
-            std::mem::size_of::<usize>() >= 8,
-            "mouse hook encoding requires 64-bit pointer width"
-        );
+    fn qpc_now() -> i64 {
+        let mut t = 0i64;
+        let _ = unsafe { windows::Win32::System::Performance::QueryPerformanceCounter(&mut t) };
+        t
+    }
+    /// Add one call to the current window, publishing it once a second has
+    /// passed. Windows only close on input, so an idle stretch folds into the
+    /// next one.
+    fn record_hook_time(start: i64, end: i64) {
+        let ticks = (end - start).max(0) as u64;
+        let calls = TIMING_CALLS.load(Ordering::Relaxed) + 1;
+        let total = TIMING_TOTAL_TICKS.load(Ordering::Relaxed) + ticks;
+        let max = TIMING_MAX_TICKS.load(Ordering::Relaxed).max(ticks);
+
+        let window = TIMING_WINDOW_START.load(Ordering::Relaxed);
+        if window == 0 {
+            TIMING_WINDOW_START.store(start, Ordering::Relaxed);
+        } else if (end - window) as u64 >= QPC_FREQUENCY.load(Ordering::Relaxed) {
+            STATS_CALLS.store(calls, Ordering::Relaxed);
+            STATS_TOTAL_TICKS.store(total, Ordering::Relaxed);
+            STATS_MAX_TICKS.store(max, Ordering::Relaxed);
+            TIMING_WINDOW_START.store(end, Ordering::Relaxed);
+            TIMING_CALLS.store(0, Ordering::Relaxed);
+            TIMING_TOTAL_TICKS.store(0, Ordering::Relaxed);
+            TIMING_MAX_TICKS.store(0, Ordering::Relaxed);
+            return;
+        }
+        TIMING_CALLS.store(calls, Ordering::Relaxed);
+        TIMING_TOTAL_TICKS.store(total, Ordering::Relaxed);
+        TIMING_MAX_TICKS.store(max, Ordering::Relaxed);
+    }
+    pub fn set_icon_interaction(enabled: bool) {
+        ICON_INTERACTION.store(enabled, Ordering::SeqCst);
+    }
+    pub fn set_move_interval_ms(ms: u32) {
+        MOVE_INTERVAL_MS.store(ms, Ordering::Relaxed);
+    }
+    fn controller_for(slot: usize) -> isize {
+        let own = TARGETS
+            .read()
+            .ok()
+            .and_then(|t| t.get(slot).map(|t| t.controller))
+            .unwrap_or(0);
+        if own != 0 {
+            own
+        } else {
+            get_comp_controller_ptr()
+        }
+    }
+
+    #[inline]
+    unsafe fn post_mouse(slot: usize, kind: i32, vk: i32, data: u32, x: i32, y: i32) {
         let dh = DISPATCH_HWND.load(Ordering::Relaxed);
         if dh == 0 {
             return;
         }
-        let wp =
-            WPARAM((kind as u16 as usize) | ((vk as u16 as usize) << 16) | ((data as usize) << 32));
-        let lp = LPARAM(((x as i16 as u16 as u32) | ((y as i16 as u16 as u32) << 16)) as isize);
+        // Low half in WPARAM, high half in LPARAM: fits 32-bit builds too
+        let p = pack_mouse(slot, kind, vk, data, x, y);
+        let wp = WPARAM(p as u32 as usize);
+        let lp = LPARAM((p >> 32) as u32 as isize);
         let _ = PostMessageW(HWND(dh as *mut _), WM_MWP_MOUSE, wp, lp);
     }
 
+    /// Event kinds (0x0200-0x02A3) are packed as an offset from this.
+    const MOUSE_KIND_BASE: i32 = MOUSE_MOVE;
+
+    #[inline]
+    fn is_wheel(kind: i32) -> bool {
+        kind == MOUSE_WHEEL || kind == MOUSE_HWHEEL
+    }
+
+    /// x | y | kind offset | slot | vk or data. Wheel events carry no button
+    /// state and the others no data, so the two share the top 16 bits.
+    #[inline]
+    fn pack_mouse(slot: usize, kind: i32, vk: i32, data: u32, x: i32, y: i32) -> u64 {
+        let extra = if is_wheel(kind) {
+            data as u16
+        } else {
+            vk as u16
+        };
+        (x as i16 as u16 as u64)
+            | ((y as i16 as u16 as u64) << 16)
+            | (((kind - MOUSE_KIND_BASE) as u8 as u64) << 32)
+            | (((slot & 0xFF) as u64) << 40)
+            | ((extra as u64) << 48)
+    }
+
+    /// (slot, kind, vk, data, x, y); wheel deltas come back sign-extended.
+    #[inline]
+    fn unpack_mouse(p: u64) -> (usize, i32, i32, u32, i32, i32) {
+        let kind = ((p >> 32) & 0xFF) as i32 + MOUSE_KIND_BASE;
+        let extra = (p >> 48) as u16;
+        let (vk, data) = if is_wheel(kind) {
+            (MK_NONE, extra as i16 as i32 as u32)
+        } else {
+            (extra as i32, 0)
+        };
+        (
+            ((p >> 40) & 0xFF) as usize,
+            kind,
+            vk,
+            data,
+            (p & 0xFFFF) as i16 as i32,
+            ((p >> 16) & 0xFFFF) as i16 as i32,
+        )
+    }
+
+    /// x | y | vk | slot, with the top bit marking the slot as occupied.
+    #[inline]
+    fn pack_move(slot: usize, vk: i32, x: i32, y: i32) -> u64 {
+        (x as i16 as u16 as u64)
+            | ((y as i16 as u16 as u64) << 16)
+            | ((vk as u16 as u64) << 32)
+            | (((slot as u64) & 0x7FFF) << 48)
+            | (1 << 63)
+    }
+
+    #[inline]
+    fn unpack_move(p: u64) -> (usize, i32, i32, i32) {
+        (
+            ((p >> 48) & 0x7FFF) as usize,
+            ((p >> 32) & 0xFFFF) as i32,
+            (p & 0xFFFF) as i16 as i32,
+            ((p >> 16) & 0xFFFF) as i16 as i32,
+        )
+    }
+
+    /// Forward a move, or park it if one went out less than the interval ago.
+    #[inline]
+    unsafe fn post_move(slot: usize, time: u32, vk: i32, x: i32, y: i32) {
+        let interval = MOVE_INTERVAL_MS.load(Ordering::Relaxed);
+        if interval == 0 || time.wrapping_sub(LAST_MOVE_TIME.load(Ordering::Relaxed)) >= interval {
+            LAST_MOVE_TIME.store(time, Ordering::Relaxed);
+            PENDING_MOVE.store(0, Ordering::Relaxed);
+            post_mouse(slot, MOUSE_MOVE, vk, 0, x, y);
+            return;
+        }
+        // Only the newest parked move matters; arm the flush on the first one
+        if PENDING_MOVE.swap(pack_move(slot, vk, x, y), Ordering::Relaxed) == 0 {
+            let dh = DISPATCH_HWND.load(Ordering::Relaxed);
+            if dh != 0 {
+                let _ = PostMessageW(
+                    HWND(dh as *mut _),
+                    WM_MWP_ARM_FLUSH,
+                    WPARAM(interval as usize),
+                    LPARAM(0),
+                );
+            }
+        }
+    }
+
+    /// Post a parked move ahead of a button/wheel event so it isn't reordered.
+    #[inline]
+    unsafe fn flush_pending_move() {
+        let p = PENDING_MOVE.swap(0, Ordering::Relaxed);
+        if p != 0 {
+            let (slot, vk, x, y) = unpack_move(p);
+            post_mouse(slot, MOUSE_MOVE, vk, 0, x, y);
+        }
+    }
+
     unsafe extern "system" fn dispatch_wnd_proc(
         hwnd: HWND,
         msg: u32,
         wp: WPARAM,
         lp: LPARAM,
     ) -> LRESULT {
+        // The controller is only safe to touch from this thread, so every
+        // resize is posted here rather than applied by the caller
         if msg == WM_MWP_SETBOUNDS_PUB {
             let ptr = get_comp_controller_ptr();
             if ptr != 0 {
@@ -700,22 +3208,83 @@ pub mod mouse_hook {
             return LRESULT(0);
         }
         if msg == WM_MWP_MOUSE {
-            let ptr = get_comp_controller_ptr();
+            let (slot, kind, vk, data, x, y) =
+                unpack_mouse((wp.0 as u32 as u64) | ((lp.0 as u32 as u64) << 32));
+            let ptr = controller_for(slot);
             if ptr != 0 {
-                let kind = (wp.0 & 0xFFFF) as i32;
-                let vk = ((wp.0 >> 16) & 0xFFFF) as i32;
-                let data = ((wp.0 >> 32) & 0xFFFFFFFF) as u32;
-                let x = (lp.0 & 0xFFFF) as i16 as i32;
-                let y = ((lp.0 >> 16) & 0xFFFF) as i16 as i32;
-
                 // Sync cursor position before click-down events
-                if kind == MOUSE_LDOWN || kind == MOUSE_RDOWN || kind == MOUSE_MDOWN {
+                if kind == MOUSE_LDOWN
+                    || kind == MOUSE_LDBLCLK
+                    || kind == MOUSE_RDOWN
+                    || kind == MOUSE_MDOWN
+                {
                     let _ = wry::send_mouse_input_raw(ptr, MOUSE_MOVE, vk, 0, x, y);
                 }
                 let _ = wry::send_mouse_input_raw(ptr, kind, vk, data, x, y);
             }
             return LRESULT(0);
         }
+        if msg == WM_MWP_ARM_FLUSH {
+            let _ = SetTimer(hwnd, FLUSH_TIMER_ID, wp.0 as u32, None);
+            return LRESULT(0);
+        }
+        if msg == WM_TIMER && wp.0 == FLUSH_TIMER_ID {
+            let _ = KillTimer(hwnd, FLUSH_TIMER_ID);
+            // WM_TIMER only arrives once posted input is drained, so sending
+            // the parked move directly can't overtake a queued button event
+            let p = PENDING_MOVE.swap(0, Ordering::Relaxed);
+            if p != 0 {
+                let (slot, vk, x, y) = unpack_move(p);
+                let ptr = controller_for(slot);
+                if ptr != 0 {
+                    let _ = wry::send_mouse_input_raw(ptr, MOUSE_MOVE, vk, 0, x, y);
+                }
+            }
+            return LRESULT(0);
+        }
+        if msg == WM_MWP_MARK_RWHH {
+            let rwhh = HWND(wp.0 as *mut _);
+            mark_render_host(rwhh);
+            install_leave_hook(rwhh);
+            return LRESULT(0);
+        }
+        if msg == WM_DPICHANGED {
+            super::on_dpi_change();
+            return LRESULT(0);
+        }
+        if msg == WM_DISPLAYCHANGE {
+            super::on_display_change();
+            return LRESULT(0);
+        }
+        // Dark mode or accent color toggled
+        if msg == WM_SETTINGCHANGE
+            && lp.0 != 0
+            && windows::core::PCWSTR(lp.0 as *const u16)
+                .to_string()
+                .is_ok_and(|area| area == "ImmersiveColorSet")
+        {
+            super::on_theme_change();
+            return LRESULT(0);
+        }
+        // Taskbar moved, resized or auto-hide toggled
+        if msg == WM_SETTINGCHANGE
+            && wp.0 == SPI_SETWORKAREA.0 as usize
+            && super::RESPECT_TASKBAR.load(Ordering::SeqCst)
+        {
+            super::on_display_change();
+            return LRESULT(0);
+        }
+
+        const WM_POWERBROADCAST: u32 = 0x0218;
+        const PBT_APMPOWERSTATUSCHANGE: usize = 0x000A;
+
+        if msg == WM_POWERBROADCAST {
+            if wp.0 == PBT_APMPOWERSTATUSCHANGE {
+                super::on_power_status_change();
+            }
+            return LRESULT(1);
+        }
+
         // WTS session lock/unlock notifications
         const WM_WTSSESSION_CHANGE: u32 = 0x02B1;
         const WTS_SESSION_LOCK: u32 = 0x7;
@@ -726,10 +3295,12 @@ pub mod mouse_hook {
                 WTS_SESSION_LOCK => {
                     crate::window_layer::IS_SESSION_ACTIVE.store(false, Ordering::SeqCst);
                     log::info!("[session] Screen locked, hook paused");
+                    super::emit_session_state(false);
                 }
                 WTS_SESSION_UNLOCK => {
                     crate::window_layer::IS_SESSION_ACTIVE.store(true, Ordering::SeqCst);
                     log::info!("[session] Screen unlocked, hook resumed");
+                    super::emit_session_state(true);
                 }
                 _ => {}
             }
@@ -748,16 +3319,18 @@ pub mod mouse_hook {
                 ..Default::default()
             };
             let _ = RegisterClassW(&wc);
+            // Hidden top-level tool window rather than HWND_MESSAGE: message-only
+            // windows never receive broadcasts such as WM_DISPLAYCHANGE.
             if let Ok(h) = CreateWindowExW(
-                WINDOW_EX_STYLE(0),
+                WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE,
                 cls,
                 windows::core::w!(""),
-                WINDOW_STYLE(0),
+                WS_POPUP,
                 0,
                 0,
                 0,
                 0,
-                HWND_MESSAGE,
+                HWND::default(),
                 None,
                 None,
                 None,
@@ -772,6 +3345,180 @@ pub mod mouse_hook {
         }
     }
 
+    /// Mark `rwhh` and every widget under it for the leave hook, moving the
+    /// marks off a previously marked host. Widgets created later are picked up
+    /// when the host is rediscovered after a reload.
+    unsafe fn mark_render_host(rwhh: HWND) {
+        let old = MARKED_RWHH.swap(rwhh.0 as isize, Ordering::SeqCst);
+        if old != 0 && old != rwhh.0 as isize {
+            set_render_marks(HWND(old as *mut _), false);
+        }
+        set_render_marks(rwhh, true);
+    }
+
+    unsafe fn set_render_marks(rwhh: HWND, on: bool) {
+        use windows::Win32::Foundation::{BOOL, HANDLE};
+
+        unsafe extern "system" fn mark_child(child: HWND, lp: LPARAM) -> BOOL {
+            if lp.0 != 0 {
+                let _ = SetPropW(child, PROP_RENDER_CHILD, HANDLE(1 as *mut _));
+            } else {
+                let _ = RemovePropW(child, PROP_RENDER_CHILD);
+            }
+            BOOL(1)
+        }
+
+        if on {
+            let _ = SetPropW(rwhh, PROP_RENDER_HOST, HANDLE(1 as *mut _));
+        } else {
+            let _ = RemovePropW(rwhh, PROP_RENDER_HOST);
+        }
+        set_suppression_prop(rwhh, !on || LEAVE_SUPPRESSION.load(Ordering::SeqCst));
+        let _ = EnumChildWindows(rwhh, Some(mark_child), LPARAM(on as isize));
+    }
+
+    unsafe fn set_suppression_prop(rwhh: HWND, enabled: bool) {
+        use windows::Win32::Foundation::HANDLE;
+
+        if enabled {
+            let _ = RemovePropW(rwhh, PROP_SUPPRESSION_OFF);
+        } else {
+            let _ = SetPropW(rwhh, PROP_SUPPRESSION_OFF, HANDLE(1 as *mut _));
+        }
+    }
+
+    /// Switch leave suppression on the marked host; later hosts inherit it.
+    pub fn set_leave_suppression(enabled: bool) {
+        LEAVE_SUPPRESSION.store(enabled, Ordering::SeqCst);
+        let marked = MARKED_RWHH.load(Ordering::SeqCst);
+        if marked != 0 {
+            unsafe { set_suppression_prop(HWND(marked as *mut _), enabled) };
+        }
+    }
+
+    /// Load the hook DLL into the WebView2 process by hooking `rwhh`'s thread
+    /// with its `mouseleave_hook_proc` export. Replaces any earlier hook.
+    unsafe fn install_leave_hook(rwhh: HWND) {
+        use windows::Win32::Foundation::{HINSTANCE, HMODULE};
+        use windows::Win32::System::LibraryLoader::{GetProcAddress, LoadLibraryW};
+
+        unhook_leave_proc();
+        let thread_id = GetWindowThreadProcessId(rwhh, None);
+        if thread_id == 0 {
+            return;
+        }
+
+        let mut module = HMODULE(LEAVE_HOOK_MODULE.load(Ordering::SeqCst) as *mut _);
+        if module.is_invalid() {
+            let Some(path) = std::env::current_exe()
+                .ok()
+                .and_then(|exe| Some(exe.parent()?.join(LEAVE_HOOK_DLL)))
+                .filter(|p| p.exists())
+            else {
+                log::info!(
+                    "[hook] {} not found, leave suppression disabled",
+                    LEAVE_HOOK_DLL
+                );
+                return;
+            };
+            if let Err(reason) = verify_hook_dll(&path) {
+                log::warn!("[hook] Not loading {}: {}", LEAVE_HOOK_DLL, reason);
+                return;
+            }
+            module = match LoadLibraryW(&windows::core::HSTRING::from(path.as_path())) {
+                Ok(m) => m,
+                Err(e) => {
+                    log::warn!("[hook] Failed to load {}: {}", LEAVE_HOOK_DLL, e);
+                    return;
+                }
+            };
+            LEAVE_HOOK_MODULE.store(module.0 as isize, Ordering::SeqCst);
+        }
+
+        let Some(proc_addr) = GetProcAddress(module, windows::core::s!("mouseleave_hook_proc"))
+        else {
+            log::warn!(
+                "[hook] {} has no mouseleave_hook_proc export",
+                LEAVE_HOOK_DLL
+            );
+            return;
+        };
+        // The export has the HOOKPROC signature by contract with the DLL
+        let hook_proc: HOOKPROC = Some(std::mem::transmute::<
+            unsafe extern "system" fn() -> isize,
+            unsafe extern "system" fn(i32, WPARAM, LPARAM) -> LRESULT,
+        >(proc_addr));
+        match SetWindowsHookExW(WH_GETMESSAGE, hook_proc, HINSTANCE(module.0), thread_id) {
+            Ok(h) => {
+                LEAVE_HOOK.store(h.0 as isize, Ordering::SeqCst);
+                log::info!(
+                    "[hook] Leave-suppression hook installed on thread {}",
+                    thread_id
+                );
+            }
+            Err(e) => log::warn!("[hook] Leave-suppression hook failed: {}", e),
+        }
+    }
+
+    /// Compare the DLL against the SHA-256 `build.rs` embedded, before it is
+    /// loaded into this process and the WebView2 one. Err says why not.
+    fn verify_hook_dll(path: &std::path::Path) -> Result<(), String> {
+        use sha2::{Digest, Sha256};
+
+        let expected = env!("MWP_HOOK_SHA256");
+        if expected.is_empty() {
+            return Err("no hash was embedded at build time".into());
+        }
+        let bytes = std::fs::read(path).map_err(|e| format!("unreadable: {}", e))?;
+        let actual: String = Sha256::digest(bytes)
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        if actual != expected {
+            return Err(format!(
+                "hash {} does not match the built {}",
+                actual, expected
+            ));
+        }
+        Ok(())
+    }
+
+    unsafe fn unhook_leave_proc() {
+        let h = LEAVE_HOOK.swap(0, Ordering::SeqCst);
+        if h != 0 {
+            let _ = UnhookWindowsHookEx(HHOOK(h as *mut _));
+        }
+    }
+
+    /// Unhook the leave-suppression DLL and clear the render host marks.
+    pub fn remove_leave_hook() {
+        unsafe {
+            unhook_leave_proc();
+            let marked = MARKED_RWHH.swap(0, Ordering::SeqCst);
+            if marked != 0 {
+                set_render_marks(HWND(marked as *mut _), false);
+            }
+        }
+    }
+
+    /// Undo `init_dispatch_window`: unregister session notifications, destroy
+    /// the window and its class so a later init starts clean.
+    pub fn destroy_dispatch_window() {
+        let dh = DISPATCH_HWND.swap(0, Ordering::SeqCst);
+        if dh == 0 {
+            return;
+        }
+        unsafe {
+            use windows::Win32::System::RemoteDesktop::WTSUnRegisterSessionNotification;
+            let h = HWND(dh as *mut _);
+            let _ = WTSUnRegisterSessionNotification(h);
+            // WM_CLOSE lets DefWindowProc destroy it on its owning thread;
+            // DestroyWindow from any other thread fails.
+            let _ = SendMessageW(h, WM_CLOSE, WPARAM(0), LPARAM(0));
+            let _ = UnregisterClassW(windows::core::w!("MWP_MouseDispatch"), None);
+        }
+    }
+
     #[inline]
     unsafe fn get_parent_process_id(pid: u32) -> Option<u32> {
         use windows::Win32::System::Diagnostics::ToolHelp::{
@@ -806,34 +3553,77 @@ pub mod mouse_hook {
         None
     }
 
+    /// Whether `pt` falls in an exclusion rect. Retries if a write races the read.
+    #[inline]
+    fn is_excluded(pt: windows::Win32::Foundation::POINT) -> bool {
+        use std::sync::atomic::fence;
+
+        loop {
+            let seq = EXCLUSION_SEQ.load(Ordering::Acquire);
+            if seq & 1 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            let count = EXCLUSION_COUNT.load(Ordering::Relaxed) as usize;
+            let hit = EXCLUSION_RECTS.iter().take(count).any(|r| {
+                let [l, t, rt, b] = [0, 1, 2, 3].map(|i| r[i].load(Ordering::Relaxed));
+                pt.x >= l && pt.x < rt && pt.y >= t && pt.y < b
+            });
+            fence(Ordering::Acquire);
+            if EXCLUSION_SEQ.load(Ordering::Relaxed) == seq {
+                return hit;
+            }
+        }
+    }
+
+    /// The target whose monitor contains `pt`. An empty rect (no monitors
+    /// enumerated) matches everywhere.
     #[inline]
-    unsafe fn is_over_desktop(hwnd_under: HWND) -> bool {
-        let tp = HWND(TARGET_PARENT_HWND.load(Ordering::Relaxed) as *mut _);
+    fn target_at(pt: windows::Win32::Foundation::POINT) -> Option<(usize, HookTarget)> {
+        let targets = TARGETS.read().ok()?;
+        targets
+            .iter()
+            .position(|t| {
+                let r = t.rect;
+                (r.left == r.right && r.top == r.bottom)
+                    || (pt.x >= r.left && pt.x < r.right && pt.y >= r.top && pt.y < r.bottom)
+            })
+            .map(|i| (i, targets[i]))
+    }
+
+    /// If the cursor is over the desktop, the slot and target to forward to.
+    #[inline]
+    unsafe fn is_over_desktop(
+        hwnd_under: HWND,
+        pt: windows::Win32::Foundation::POINT,
+    ) -> Option<(usize, HookTarget)> {
+        let hit = target_at(pt)?;
+        let tp = HWND(hit.1.parent as *mut _);
         let rwhh = HWND(CHROME_RWHH.load(Ordering::Relaxed) as *mut _);
-        let wv = HWND(WEBVIEW_HWND.load(Ordering::Relaxed) as *mut _);
+        let wv = HWND(hit.1.webview as *mut _);
         let pm = HWND(PROGMAN_HWND.load(Ordering::Relaxed) as *mut _);
         let dc = HWND(DESKTOP_CORE_HWND.load(Ordering::Relaxed) as *mut _);
         let slv = HWND(SYSLISTVIEW_HWND.load(Ordering::Relaxed) as *mut _);
 
         // Fast path: known HWNDs (includes cached desktop CoreWindow + SysListView32)
         if !rwhh.is_invalid() && hwnd_under == rwhh {
-            return true;
+            return Some(hit);
         }
         if !slv.is_invalid() && hwnd_under == slv {
-            return true;
+            return Some(hit);
         }
         if !dc.is_invalid() && hwnd_under == dc {
-            return true;
+            return Some(hit);
         }
         if hwnd_under == tp || hwnd_under == wv || hwnd_under == pm {
-            return true;
+            return Some(hit);
         }
         if !pm.is_invalid() && IsChild(pm, hwnd_under).as_bool() {
-            return true;
+            return Some(hit);
         }
         // Also check if hwnd_under is a child of the target parent (WorkerW)
         if !tp.is_invalid() && IsChild(tp, hwnd_under).as_bool() {
-            return true;
+            return Some(hit);
         }
 
         // Slow path: zero-allocation class name checks
@@ -844,7 +3634,7 @@ pub mod mouse_hook {
                 GetWindowThreadProcessId(hwnd_under, Some(&mut pid));
                 if pid == exp_pid {
                     DESKTOP_CORE_HWND.store(hwnd_under.0 as isize, Ordering::Relaxed);
-                    return true;
+                    return Some(hit);
                 }
             }
         }
@@ -869,11 +3659,21 @@ pub mod mouse_hook {
                         hwnd_under.0 as isize
                     );
                     CHROME_RWHH.store(hwnd_under.0 as isize, Ordering::Relaxed);
-                    return true;
+                    // Props are set off the hook thread
+                    let dh = DISPATCH_HWND.load(Ordering::Relaxed);
+                    if dh != 0 {
+                        let _ = PostMessageW(
+                            HWND(dh as *mut _),
+                            WM_MWP_MARK_RWHH,
+                            WPARAM(hwnd_under.0 as usize),
+                            LPARAM(0),
+                        );
+                    }
+                    return Some(hit);
                 }
             }
         }
-        false
+        None
     }
 
     /// Check if screen point is over a desktop icon via LVM_HITTEST.
@@ -993,39 +3793,79 @@ pub mod mouse_hook {
         ht_out.i_item >= 0
     }
 
+    /// Double-click detection for left-button downs on the desktop. Runs once
+    /// per event so the webview and SysListView32 paths agree on the result
+    /// (PostMessage/SendMouseInput bypass native double-click detection).
+    unsafe fn detect_double_click(info_hook: &MSLLHOOKSTRUCT) -> bool {
+        static LAST_DOWN_TIME: AtomicU32 = AtomicU32::new(0);
+        static LAST_DOWN_X: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(0);
+        static LAST_DOWN_Y: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(0);
+
+        let now = info_hook.time;
+        let dt = now.saturating_sub(LAST_DOWN_TIME.load(Ordering::Relaxed));
+        let dx = (info_hook.pt.x - LAST_DOWN_X.load(Ordering::Relaxed)).abs();
+        let dy = (info_hook.pt.y - LAST_DOWN_Y.load(Ordering::Relaxed)).abs();
+
+        if dt > 0
+            && dt <= DBLCLICK_TIME.load(Ordering::Relaxed)
+            && dx <= DBLCLICK_CX.load(Ordering::Relaxed)
+            && dy <= DBLCLICK_CY.load(Ordering::Relaxed)
+        {
+            // Reset so a triple click is double + single, as natively
+            LAST_DOWN_TIME.store(0, Ordering::Relaxed);
+            true
+        } else {
+            LAST_DOWN_TIME.store(now, Ordering::Relaxed);
+            LAST_DOWN_X.store(info_hook.pt.x, Ordering::Relaxed);
+            LAST_DOWN_Y.store(info_hook.pt.y, Ordering::Relaxed);
+            false
+        }
+    }
+
     #[inline]
-    unsafe fn forward(msg: u32, info_hook: &MSLLHOOKSTRUCT, cx: i32, cy: i32) {
+    unsafe fn forward(
+        slot: usize,
+        msg: u32,
+        info_hook: &MSLLHOOKSTRUCT,
+        cx: i32,
+        cy: i32,
+        dbl: bool,
+    ) {
+        if msg != WM_MOUSEMOVE {
+            flush_pending_move();
+        }
         match msg {
-            WM_MOUSEMOVE => post_mouse(
-                MOUSE_MOVE,
+            WM_MOUSEMOVE => post_move(
+                slot,
+                info_hook.time,
                 DRAG_VK.load(Ordering::Relaxed) as i32,
-                0,
                 cx,
                 cy,
             ),
             WM_LBUTTONDOWN => {
                 DRAG_VK.store(MK_LBUTTON as isize, Ordering::Relaxed);
-                post_mouse(MOUSE_LDOWN, MK_LBUTTON, 0, cx, cy);
+                let kind = if dbl { MOUSE_LDBLCLK } else { MOUSE_LDOWN };
+                post_mouse(slot, kind, MK_LBUTTON, 0, cx, cy);
             }
             WM_LBUTTONUP => {
                 DRAG_VK.store(0, Ordering::Relaxed);
-                post_mouse(MOUSE_LUP, MK_NONE, 0, cx, cy);
+                post_mouse(slot, MOUSE_LUP, MK_NONE, 0, cx, cy);
             }
             WM_RBUTTONDOWN => {
                 DRAG_VK.store(MK_RBUTTON as isize, Ordering::Relaxed);
-                post_mouse(MOUSE_RDOWN, MK_RBUTTON, 0, cx, cy);
+                post_mouse(slot, MOUSE_RDOWN, MK_RBUTTON, 0, cx, cy);
             }
             WM_RBUTTONUP => {
                 DRAG_VK.store(0, Ordering::Relaxed);
-                post_mouse(MOUSE_RUP, MK_NONE, 0, cx, cy);
+                post_mouse(slot, MOUSE_RUP, MK_NONE, 0, cx, cy);
             }
             WM_MBUTTONDOWN => {
                 DRAG_VK.store(MK_MBUTTON as isize, Ordering::Relaxed);
-                post_mouse(MOUSE_MDOWN, MK_MBUTTON, 0, cx, cy);
+                post_mouse(slot, MOUSE_MDOWN, MK_MBUTTON, 0, cx, cy);
             }
             WM_MBUTTONUP => {
                 DRAG_VK.store(0, Ordering::Relaxed);
-                post_mouse(MOUSE_MUP, MK_NONE, 0, cx, cy);
+                post_mouse(slot, MOUSE_MUP, MK_NONE, 0, cx, cy);
             }
             WM_MOUSEWHEEL | WM_MOUSEHWHEEL => {
                 let kind = if msg == WM_MOUSEWHEEL {
@@ -1034,6 +3874,7 @@ pub mod mouse_hook {
                     MOUSE_HWHEEL
                 };
                 post_mouse(
+                    slot,
                     kind,
                     MK_NONE,
                     (info_hook.mouseData >> 16) as i16 as i32 as u32,
@@ -1043,10 +3884,27 @@ pub mod mouse_hook {
             }
             _ => {}
         }
+        // Touch and pen only reach us as promoted mouse input (tap = left,
+        // press-and-hold = right), which the LL hook sees tagged with
+        // MI_WP_SIGNATURE. Raw WM_POINTER goes to Explorer's desktop windows
+        // and RegisterPointerInputTarget needs UIAccess, so multi-touch and
+        // pressure aren't available. A lifted finger has no hover, so end it
+        // or :hover sticks
+        if (msg == WM_LBUTTONUP || msg == WM_RBUTTONUP)
+            && crate::commands_core::pointer_source(info_hook.dwExtraInfo)
+                == crate::commands_core::PointerSource::Touch
+        {
+            post_mouse(slot, MOUSE_LEAVE, MK_NONE, 0, cx, cy);
+        }
     }
 
     pub fn start_hook_thread() {
-        std::thread::spawn(|| {
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            unsafe {
+                use windows::Win32::System::Threading::GetCurrentThreadId;
+                HOOK_THREAD_ID.store(GetCurrentThreadId(), Ordering::SeqCst);
+            }
             unsafe {
                 use windows::Win32::System::Com::{CoInitializeEx, COINIT_APARTMENTTHREADED};
                 let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
@@ -1060,7 +3918,7 @@ pub mod mouse_hook {
             }
 
             /// Post a mouse event to SysListView32, with double-click synthesis and key state.
-            unsafe fn post_to_slv(slv: HWND, msg: u32, info_hook: &MSLLHOOKSTRUCT) {
+            unsafe fn post_to_slv(slv: HWND, msg: u32, info_hook: &MSLLHOOKSTRUCT, dbl: bool) {
                 use windows::Win32::Graphics::Gdi::ScreenToClient;
 
                 if !IsWindowVisible(slv).as_bool() {
@@ -1079,33 +3937,11 @@ pub mod mouse_hook {
                 };
 
                 // Synthesize double-click (PostMessage bypasses native detection)
-                let mut out_msg = msg;
-                if msg == WM_LBUTTONDOWN {
-                    static LAST_DOWN_TIME: std::sync::atomic::AtomicU32 =
-                        std::sync::atomic::AtomicU32::new(0);
-                    static LAST_DOWN_X: std::sync::atomic::AtomicI32 =
-                        std::sync::atomic::AtomicI32::new(0);
-                    static LAST_DOWN_Y: std::sync::atomic::AtomicI32 =
-                        std::sync::atomic::AtomicI32::new(0);
-
-                    let now = info_hook.time;
-                    let dt = now.saturating_sub(LAST_DOWN_TIME.load(Ordering::Relaxed));
-                    let dx = (info_hook.pt.x - LAST_DOWN_X.load(Ordering::Relaxed)).abs();
-                    let dy = (info_hook.pt.y - LAST_DOWN_Y.load(Ordering::Relaxed)).abs();
-
-                    if dt > 0
-                        && dt <= DBLCLICK_TIME.load(Ordering::Relaxed)
-                        && dx <= DBLCLICK_CX.load(Ordering::Relaxed)
-                        && dy <= DBLCLICK_CY.load(Ordering::Relaxed)
-                    {
-                        out_msg = WM_LBUTTONDBLCLK;
-                        LAST_DOWN_TIME.store(0, Ordering::Relaxed);
-                    } else {
-                        LAST_DOWN_TIME.store(now, Ordering::Relaxed);
-                        LAST_DOWN_X.store(info_hook.pt.x, Ordering::Relaxed);
-                        LAST_DOWN_Y.store(info_hook.pt.y, Ordering::Relaxed);
-                    }
-                }
+                let out_msg = if msg == WM_LBUTTONDOWN && dbl {
+                    WM_LBUTTONDBLCLK
+                } else {
+                    msg
+                };
 
                 let slv_wparam = {
                     use windows::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
@@ -1170,6 +4006,16 @@ pub mod mouse_hook {
                 wparam: WPARAM,
                 lparam: LPARAM,
             ) -> LRESULT {
+                if !TIMING_ENABLED.load(Ordering::Relaxed) {
+                    return hook_body(code, wparam, lparam);
+                }
+                let start = qpc_now();
+                let result = hook_body(code, wparam, lparam);
+                record_hook_time(start, qpc_now());
+                result
+            }
+
+            unsafe fn hook_body(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
                 let hook_h = HHOOK(
                     crate::window_layer::HOOK_HANDLE_GLOBAL.load(Ordering::Relaxed) as *mut _,
                 );
@@ -1183,10 +4029,20 @@ pub mod mouse_hook {
                     return CallNextHookEx(hook_h, code, wparam, lparam);
                 }
 
+                if FORWARDING_PAUSED.load(Ordering::Relaxed) {
+                    return CallNextHookEx(hook_h, code, wparam, lparam);
+                }
+
                 let info_hook = *(lparam.0 as *const MSLLHOOKSTRUCT);
                 let hwnd_under = WindowFromPoint(info_hook.pt);
                 let msg = wparam.0 as u32;
-                let slv_raw = SYSLISTVIEW_HWND.load(Ordering::Relaxed);
+                let icons = ICON_INTERACTION.load(Ordering::Relaxed);
+                // 0 skips the icon hit test and the SysListView32 fallback below
+                let slv_raw = if icons {
+                    SYSLISTVIEW_HWND.load(Ordering::Relaxed)
+                } else {
+                    0
+                };
 
                 // ── Active native drag: forward to SysListView32, skip webview ──
                 if NATIVE_DRAG.load(Ordering::Relaxed) {
@@ -1250,11 +4106,32 @@ pub mod mouse_hook {
                     return LRESULT(1);
                 }
 
-                // ── Not over desktop: pass through ──
-                if !is_over_desktop(hwnd_under) {
+                // ── Over an exclusion rect: leave the input to the window there ──
+                if is_excluded(info_hook.pt) {
+                    return CallNextHookEx(hook_h, code, wparam, lparam);
+                }
+
+                // ── Detached layer: only our own window needs forwarding ──
+                // (composition hosting gets no native input), never the icons.
+                if !DESKTOP_MODE.load(Ordering::Relaxed) {
+                    if hwnd_under.0 as isize == wv_raw {
+                        use windows::Win32::Graphics::Gdi::ScreenToClient;
+                        let dbl = msg == WM_LBUTTONDOWN && detect_double_click(&info_hook);
+                        let mut cp = info_hook.pt;
+                        let _ = ScreenToClient(HWND(wv_raw as *mut _), &mut cp);
+                        forward(0, msg, &info_hook, cp.x, cp.y, dbl);
+                    }
                     return CallNextHookEx(hook_h, code, wparam, lparam);
                 }
 
+                // ── Not over desktop: pass through ──
+                let Some((slot, target)) = is_over_desktop(hwnd_under, info_hook.pt) else {
+                    return CallNextHookEx(hook_h, code, wparam, lparam);
+                };
+
+                // Decided once here so the icon and webview paths can't disagree
+                let dbl = msg == WM_LBUTTONDOWN && detect_double_click(&info_hook);
+
                 // ── On desktop: check if button-down is on an icon ──
                 if (msg == WM_LBUTTONDOWN || msg == WM_RBUTTONDOWN) && slv_raw != 0 {
                     log::info!(
@@ -1272,7 +4149,10 @@ pub mod mouse_hook {
                             info_hook.pt.x,
                             info_hook.pt.y,
                         );
-                        post_to_slv(HWND(slv_raw as *mut _), msg, &info_hook);
+                        // The page still gets the last parked move, so its
+                        // hover state matches where the click landed
+                        flush_pending_move();
+                        post_to_slv(HWND(slv_raw as *mut _), msg, &info_hook, dbl);
                         // Consume: Chrome must not see the click (SetCapture/
                         // SetFocus would steal the gesture from SysListView32).
                         return LRESULT(1);
@@ -1282,27 +4162,78 @@ pub mod mouse_hook {
                 // ── Normal desktop interaction (wallpaper) ──
                 use windows::Win32::Graphics::Gdi::ScreenToClient;
                 let mut cp = info_hook.pt;
-                let _ = ScreenToClient(HWND(wv_raw as *mut _), &mut cp);
-                forward(msg, &info_hook, cp.x, cp.y);
+                let _ = ScreenToClient(HWND(target.webview as *mut _), &mut cp);
+                forward(slot, msg, &info_hook, cp.x, cp.y, dbl);
 
                 // PostMessageW fallback when native delivery won't reach SysListView32
                 if (hwnd_under.0 as isize) != slv_raw && slv_raw != 0 {
-                    post_to_slv(HWND(slv_raw as *mut _), msg, &info_hook);
+                    post_to_slv(HWND(slv_raw as *mut _), msg, &info_hook, dbl);
+                }
+
+                // Icons off: keep the native click from SysListView32 too. Moves
+                // pass, since swallowing them would freeze the cursor.
+                if !icons
+                    && msg != WM_MOUSEMOVE
+                    && hwnd_under.0 as isize == SYSLISTVIEW_HWND.load(Ordering::Relaxed)
+                {
+                    return LRESULT(1);
                 }
 
                 CallNextHookEx(hook_h, code, wparam, lparam)
             }
 
             unsafe {
+                let mut msg = MSG::default();
+                // Create the message queue so a WM_QUIT from stop_hook_thread
+                // can't be posted before it exists and get lost
+                let _ = PeekMessageW(&mut msg, HWND::default(), WM_USER, WM_USER, PM_NOREMOVE);
                 if let Ok(h) = SetWindowsHookExW(WH_MOUSE_LL, Some(hook_proc), None, 0) {
                     crate::window_layer::HOOK_HANDLE_GLOBAL.store(h.0 as isize, Ordering::SeqCst);
                 }
-                let mut msg = MSG::default();
+                let _ = started_tx.send(());
                 while GetMessageW(&mut msg, HWND::default(), 0, 0).into() {
                     let _ = TranslateMessage(&msg);
                     DispatchMessageW(&msg);
                 }
             }
         });
+        // The queue and hook must exist before a stop can be requested
+        let _ = started_rx.recv();
+    }
+
+    /// Unhook and end the hook thread's message loop, so a later
+    /// `start_hook_thread` can't leave two hooks running.
+    pub fn stop_hook_thread() {
+        unsafe {
+            let h = crate::window_layer::HOOK_HANDLE_GLOBAL.swap(0, Ordering::SeqCst);
+            if h != 0 {
+                let _ = UnhookWindowsHookEx(HHOOK(h as *mut _));
+            }
+            let tid = HOOK_THREAD_ID.swap(0, Ordering::SeqCst);
+            if tid != 0 {
+                let _ = PostThreadMessageW(tid, WM_QUIT, WPARAM(0), LPARAM(0));
+            }
+        }
+    }
+
+    /// Forget every window, controller and gesture the hook has cached, as on
+    /// a fresh launch. User preferences (exclusion rects, move rate, leave
+    /// suppression) are kept.
+    pub fn reset_state() {
+        WEBVIEW_HWND.store(0, Ordering::SeqCst);
+        SYSLISTVIEW_HWND.store(0, Ordering::SeqCst);
+        TARGETS.write().unwrap().clear();
+        PROGMAN_HWND.store(0, Ordering::SeqCst);
+        EXPLORER_PID.store(0, Ordering::SeqCst);
+        DESKTOP_CORE_HWND.store(0, Ordering::SeqCst);
+        COMP_CONTROLLER_PTR.store(0, Ordering::SeqCst);
+        DRAG_VK.store(0, Ordering::SeqCst);
+        CHROME_RWHH.store(0, Ordering::SeqCst);
+        NATIVE_DRAG.store(false, Ordering::SeqCst);
+        INJECTING_FOR_DRAG.store(false, Ordering::SeqCst);
+        DESKTOP_MODE.store(true, Ordering::SeqCst);
+        FORWARDING_PAUSED.store(false, Ordering::SeqCst);
+        PENDING_MOVE.store(0, Ordering::SeqCst);
+        LAST_MOVE_TIME.store(0, Ordering::SeqCst);
     }
 }